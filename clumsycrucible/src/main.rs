@@ -1,6 +1,6 @@
 use std::{
     cmp::Reverse,
-    collections::{BinaryHeap, HashMap, HashSet},
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
     fs,
 };
 
@@ -33,7 +33,18 @@ struct Span {
 
 impl Span {
     fn get_options(self, min_span: u32) -> Vec<Span> {
-        if self.len > 0 && self.len < min_span {
+        if self.len == 0 {
+            return [
+                Direction::Up,
+                Direction::Down,
+                Direction::Left,
+                Direction::Right,
+            ]
+            .into_iter()
+            .map(|dir| Span { len: 1, dir })
+            .collect();
+        }
+        if self.len < min_span {
             return vec![Span {
                 len: self.len + 1,
                 dir: self.dir,
@@ -98,32 +109,119 @@ impl Span {
             ],
         }
     }
+
+    fn can_stop(self, min_span: u32) -> bool {
+        self.len == 0 || self.len >= min_span
+    }
 }
 
-fn parse_grid(input: &str) -> Grid<u32> {
+// (x, y)
+type Position = (i32, i32);
+
+// Digits are heat loss, anything else is an obstacle the crucible can't enter.
+fn parse_grid(input: &str) -> Grid<Option<u32>> {
     let lines = input.lines().collect::<Vec<_>>();
     Grid::from_vec(
         lines
             .iter()
-            .flat_map(|line| line.chars().map(|c| c.to_digit(10).unwrap()))
+            .flat_map(|line| line.chars().map(|c| c.to_digit(10)))
             .collect::<Vec<_>>(),
         lines[0].len(),
     )
 }
 
-fn min_cost(grid: &Grid<u32>, min_span: u32, max_span: u32) -> u32 {
+fn get_cell(grid: &Grid<Option<u32>>, (x, y): Position) -> Option<u32> {
+    if x < 0 || y < 0 {
+        return None;
+    }
+    grid.get(y as usize, x as usize).copied().flatten()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    Bounded,
+    // Leaving one edge re-enters the opposite one.
+    Toroidal,
+    // The map repeats, every tile step away from the original adding `increment` to the loss of
+    // each cell. Tiles more than `max_tile_distance` steps away can't be entered, so that a search
+    // for an unreachable goal ends.
+    Tiled {
+        increment: u32,
+        max_tile_distance: u32,
+    },
+}
+
+impl Topology {
+    // Where the crucible ends up when moving to `position`, with the loss of entering it.
+    fn enter(self, grid: &Grid<Option<u32>>, (x, y): Position) -> Option<(Position, u32)> {
+        let (cols, rows) = (grid.cols() as i32, grid.rows() as i32);
+        match self {
+            Topology::Bounded => get_cell(grid, (x, y)).map(|loss| ((x, y), loss)),
+            Topology::Toroidal => {
+                let position = (x.rem_euclid(cols), y.rem_euclid(rows));
+                get_cell(grid, position).map(|loss| (position, loss))
+            }
+            Topology::Tiled {
+                increment,
+                max_tile_distance,
+            } => {
+                let tile_distance =
+                    x.div_euclid(cols).unsigned_abs() + y.div_euclid(rows).unsigned_abs();
+                if tile_distance > max_tile_distance {
+                    return None;
+                }
+                get_cell(grid, (x.rem_euclid(cols), y.rem_euclid(rows)))
+                    .map(|loss| ((x, y), loss + increment * tile_distance))
+            }
+        }
+    }
+}
+
+// (x, y, span that brought the crucible here)
+type State = (i32, i32, Span);
+
+// Every state reachable in one step from `(x, y, span)`, with the loss of entering it.
+fn get_moves(
+    grid: &Grid<Option<u32>>,
+    topology: Topology,
+    (x, y, span): State,
+    min_span: u32,
+    max_span: u32,
+) -> Vec<(State, u32)> {
+    span.get_options(min_span)
+        .into_iter()
+        .filter(|option| option.len <= max_span)
+        .filter_map(|option| {
+            let (x_offset, y_offset) = option.dir.get_offset();
+            topology
+                .enter(grid, (x + x_offset, y + y_offset))
+                .map(|((new_x, new_y), cell_loss)| ((new_x, new_y, option), cell_loss))
+        })
+        .collect()
+}
+
+const START_SPAN: Span = Span {
+    len: 0,
+    dir: Direction::Right,
+};
+
+// Runs Dijkstra from `start`, calling `on_stop` with every position the crucible can stop at in
+// increasing order of loss. The search ends early once `on_stop` returns true.
+fn search(
+    grid: &Grid<Option<u32>>,
+    topology: Topology,
+    start: Position,
+    min_span: u32,
+    max_span: u32,
+    mut on_stop: impl FnMut(Position, u32) -> bool,
+) {
+    let Some((start, _)) = topology.enter(grid, start) else {
+        return;
+    };
     // (loss, x, y)
-    let mut priority_queue = BinaryHeap::from(vec![(
-        Reverse(0),
-        0_i32,
-        0_i32,
-        Span {
-            len: 0,
-            dir: Direction::Right,
-        },
-    )]);
-    let mut current_bests: HashMap<(i32, i32, Span), u32> = HashMap::new();
-    let mut visited: HashSet<(i32, i32, Span)> = HashSet::new();
+    let mut priority_queue = BinaryHeap::from(vec![(Reverse(0), start.0, start.1, START_SPAN)]);
+    let mut current_bests: HashMap<State, u32> = HashMap::new();
+    let mut visited: HashSet<State> = HashSet::new();
 
     while let Some((Reverse(loss), x, y, span)) = priority_queue.pop() {
         if visited.contains(&(x, y, span)) {
@@ -136,33 +234,317 @@ fn min_cost(grid: &Grid<u32>, min_span: u32, max_span: u32) -> u32 {
         }
         current_bests.insert((x, y, span), loss);
         visited.insert((x, y, span));
-        if span.len >= min_span && x == grid.cols() as i32 - 1 && y == grid.rows() as i32 - 1 {
-            return loss;
+        if span.can_stop(min_span) && on_stop((x, y), loss) {
+            return;
         }
 
-        for option in span.get_options(min_span) {
-            let (x_offset, y_offset) = option.dir.get_offset();
-            let (new_x, new_y) = (x + x_offset, y + y_offset);
-            if new_x < 0
-                || new_y < 0
-                || new_x >= grid.cols() as i32
-                || new_y >= grid.rows() as i32
-                || option.len > max_span
+        for ((new_x, new_y, option), cell_loss) in
+            get_moves(grid, topology, (x, y, span), min_span, max_span)
+        {
+            if !visited.contains(&(new_x, new_y, option)) {
+                priority_queue.push((Reverse(loss + cell_loss), new_x, new_y, option));
+            }
+        }
+    }
+}
+
+// Cheapest goal reachable from `start`, as (goal, loss).
+pub fn min_cost_to_any(
+    grid: &Grid<Option<u32>>,
+    topology: Topology,
+    start: Position,
+    goals: &[Position],
+    min_span: u32,
+    max_span: u32,
+) -> Option<(Position, u32)> {
+    // Goals on obstacles or outside the map can never be stopped at
+    if goals
+        .iter()
+        .all(|&goal| topology.enter(grid, goal).is_none())
+    {
+        return None;
+    }
+    let mut result = None;
+    search(
+        grid,
+        topology,
+        start,
+        min_span,
+        max_span,
+        |position, loss| {
+            if goals.contains(&position) {
+                result = Some((position, loss));
+                return true;
+            }
+            false
+        },
+    );
+    result
+}
+
+// Minimal loss to reach (and stop at) every cell from `start`, `None` where unreachable. The tiled
+// map reaches past the original cells, so it isn't supported here.
+pub fn cost_map(
+    grid: &Grid<Option<u32>>,
+    topology: Topology,
+    start: Position,
+    min_span: u32,
+    max_span: u32,
+) -> Grid<Option<u32>> {
+    assert!(
+        !matches!(topology, Topology::Tiled { .. }),
+        "Cost map of a tiled map doesn't fit the grid"
+    );
+    let mut costs = Grid::new(grid.rows(), grid.cols());
+    search(grid, topology, start, min_span, max_span, |(x, y), loss| {
+        let cost: &mut Option<u32> = &mut costs[(y as usize, x as usize)];
+        if cost.is_none() {
+            *cost = Some(loss);
+        }
+        false
+    });
+    costs
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl Axis {
+    fn turn(self) -> Axis {
+        match self {
+            Axis::Horizontal => Axis::Vertical,
+            Axis::Vertical => Axis::Horizontal,
+        }
+    }
+
+    fn directions(self) -> [Direction; 2] {
+        match self {
+            Axis::Horizontal => [Direction::Left, Direction::Right],
+            Axis::Vertical => [Direction::Up, Direction::Down],
+        }
+    }
+}
+
+// Same answer as `min_cost_to_any`, but every move is a whole straight run of `min_span..=max_span`
+// cells followed by a turn, so the state is just the position and the axis of the last run.
+pub fn min_cost_runs(
+    grid: &Grid<Option<u32>>,
+    start: Position,
+    goals: &[Position],
+    min_span: u32,
+    max_span: u32,
+) -> Option<(Position, u32)> {
+    get_cell(grid, start)?;
+    let cols = grid.cols();
+    let index = |(x, y): Position, axis: Axis| (y as usize * cols + x as usize) * 2 + axis as usize;
+    let mut bests = vec![u32::MAX; grid.rows() * cols * 2];
+    let mut visited = vec![false; grid.rows() * cols * 2];
+
+    // (loss, x, y, axis of the last run)
+    let mut priority_queue = BinaryHeap::new();
+    for axis in [Axis::Horizontal, Axis::Vertical] {
+        bests[index(start, axis)] = 0;
+        priority_queue.push((Reverse(0), start.0, start.1, axis));
+    }
+
+    while let Some((Reverse(loss), x, y, axis)) = priority_queue.pop() {
+        if visited[index((x, y), axis)] {
+            continue;
+        }
+        visited[index((x, y), axis)] = true;
+        if goals.contains(&(x, y)) {
+            return Some(((x, y), loss));
+        }
+
+        let next_axis = axis.turn();
+        for dir in next_axis.directions() {
+            let (x_offset, y_offset) = dir.get_offset();
+            let mut run_loss = loss;
+            for len in 1..=max_span as i32 {
+                let position = (x + x_offset * len, y + y_offset * len);
+                let Some(cell_loss) = get_cell(grid, position) else {
+                    break;
+                };
+                run_loss += cell_loss;
+                if len < min_span as i32 {
+                    continue;
+                }
+                let next = index(position, next_axis);
+                if !visited[next] && run_loss < bests[next] {
+                    bests[next] = run_loss;
+                    priority_queue.push((Reverse(run_loss), position.0, position.1, next_axis));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub cells: Vec<Position>,
+    pub loss: u32,
+}
+
+// States along a route, each with the loss accumulated on arriving there.
+type StatePath = Vec<(State, u32)>;
+
+// Cheapest path from `from` to a stoppable `goal` state that avoids the banned states and moves.
+fn shortest_state_path(
+    grid: &Grid<Option<u32>>,
+    from: State,
+    goal: Position,
+    min_span: u32,
+    max_span: u32,
+    banned_states: &HashSet<State>,
+    banned_moves: &HashSet<(State, State)>,
+) -> Option<StatePath> {
+    let mut priority_queue = BinaryHeap::from(vec![(Reverse(0), from)]);
+    let mut current_bests: HashMap<State, u32> = HashMap::from([(from, 0)]);
+    let mut previous: HashMap<State, State> = HashMap::new();
+    let mut visited: HashSet<State> = HashSet::new();
+
+    while let Some((Reverse(loss), state)) = priority_queue.pop() {
+        if !visited.insert(state) {
+            continue;
+        }
+        if (state.0, state.1) == goal && state.2.can_stop(min_span) {
+            let mut path = vec![(state, loss)];
+            while let Some(prev) = previous.get(&path.last().unwrap().0) {
+                path.push((*prev, current_bests[prev]));
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for (next, cell_loss) in get_moves(grid, Topology::Bounded, state, min_span, max_span) {
+            if visited.contains(&next)
+                || banned_states.contains(&next)
+                || banned_moves.contains(&(state, next))
             {
                 continue;
             }
-            if !visited.contains(&(new_x, new_y, option)) {
-                priority_queue.push((
-                    Reverse(loss + grid[(new_y as usize, new_x as usize)]),
-                    new_x,
-                    new_y,
-                    option,
-                ));
+            let next_loss = loss + cell_loss;
+            if current_bests
+                .get(&next)
+                .is_none_or(|best| next_loss < *best)
+            {
+                current_bests.insert(next, next_loss);
+                previous.insert(next, state);
+                priority_queue.push((Reverse(next_loss), next));
             }
         }
     }
 
-    unreachable!()
+    None
+}
+
+// The `k` cheapest distinct routes from `start` to `goal`, cheapest first, using Yen's algorithm
+// over the span-constrained state graph.
+pub fn k_cheapest_routes(
+    grid: &Grid<Option<u32>>,
+    start: Position,
+    goal: Position,
+    k: usize,
+    min_span: u32,
+    max_span: u32,
+) -> Vec<Route> {
+    let mut found: Vec<StatePath> = Vec::new();
+    if k == 0 || get_cell(grid, start).is_none() {
+        return Vec::new();
+    }
+    let start_state = (start.0, start.1, START_SPAN);
+    let no_states = HashSet::new();
+    let no_moves = HashSet::new();
+    let Some(first) = shortest_state_path(
+        grid,
+        start_state,
+        goal,
+        min_span,
+        max_span,
+        &no_states,
+        &no_moves,
+    ) else {
+        return Vec::new();
+    };
+    found.push(first);
+
+    // (loss, path)
+    let mut candidates: BinaryHeap<Reverse<(u32, StatePath)>> = BinaryHeap::new();
+    let mut seen: HashSet<Vec<State>> = HashSet::new();
+    while found.len() < k {
+        let previous = found.last().unwrap();
+        for spur_index in 0..previous.len() - 1 {
+            let (spur, spur_loss) = previous[spur_index];
+            let root = &previous[..=spur_index];
+            let banned_moves = found
+                .iter()
+                .filter(|path| path.len() > spur_index + 1 && path[..=spur_index] == *root)
+                .map(|path| (spur, path[spur_index + 1].0))
+                .collect::<HashSet<_>>();
+            let banned_states = root[..spur_index]
+                .iter()
+                .map(|&(state, _)| state)
+                .collect::<HashSet<_>>();
+
+            let Some(spur_path) = shortest_state_path(
+                grid,
+                spur,
+                goal,
+                min_span,
+                max_span,
+                &banned_states,
+                &banned_moves,
+            ) else {
+                continue;
+            };
+            let path = root[..spur_index]
+                .iter()
+                .copied()
+                .chain(
+                    spur_path
+                        .into_iter()
+                        .map(|(state, loss)| (state, spur_loss + loss)),
+                )
+                .collect::<StatePath>();
+            if seen.insert(path.iter().map(|&(state, _)| state).collect()) {
+                candidates.push(Reverse((path.last().unwrap().1, path)));
+            }
+        }
+
+        let Some(Reverse((_, path))) = candidates.pop() else {
+            break;
+        };
+        found.push(path);
+    }
+
+    found
+        .into_iter()
+        .map(|path| Route {
+            loss: path.last().unwrap().1,
+            cells: path.into_iter().map(|((x, y, _), _)| (x, y)).collect(),
+        })
+        .collect()
+}
+
+// Number of routes for each loss.
+pub fn route_loss_histogram(routes: &[Route]) -> BTreeMap<u32, usize> {
+    let mut histogram = BTreeMap::new();
+    for route in routes {
+        *histogram.entry(route.loss).or_insert(0) += 1;
+    }
+    histogram
+}
+
+fn min_cost(grid: &Grid<Option<u32>>, min_span: u32, max_span: u32) -> u32 {
+    let goal = (grid.cols() as i32 - 1, grid.rows() as i32 - 1);
+    min_cost_to_any(grid, Topology::Bounded, (0, 0), &[goal], min_span, max_span)
+        .expect("Goal is unreachable")
+        .1
 }
 
 fn part_one(input: &str) -> u32 {
@@ -220,4 +602,327 @@ mod tests {
 999999999991"#;
         assert_eq!(part_two(input), 71);
     }
+
+    #[test]
+    fn test_start_and_goals() {
+        let grid = parse_grid(TEST_INPUT);
+        let goal = (grid.cols() as i32 - 1, grid.rows() as i32 - 1);
+        assert_eq!(
+            min_cost_to_any(&grid, Topology::Bounded, goal, &[(0, 0)], 0, 3),
+            Some((
+                (0, 0),
+                102 - grid[(12, 12)].unwrap() + grid[(0, 0)].unwrap()
+            ))
+        );
+        assert_eq!(
+            min_cost_to_any(&grid, Topology::Bounded, (0, 0), &[(0, 0)], 4, 10),
+            Some(((0, 0), 0))
+        );
+        assert_eq!(
+            min_cost_to_any(&grid, Topology::Bounded, (0, 0), &[(5, 0), (1, 0)], 0, 3),
+            Some(((1, 0), 4))
+        );
+        assert_eq!(
+            min_cost_to_any(&grid, Topology::Bounded, (0, 0), &[(1, 0)], 4, 10),
+            cost_map(&grid, Topology::Bounded, (0, 0), 4, 10)[(0, 1)].map(|loss| ((1, 0), loss))
+        );
+    }
+
+    #[test]
+    fn test_obstacles() {
+        let input = r#"1111
+1##1
+1#91
+1111"#;
+        let grid = parse_grid(input);
+        assert_eq!(min_cost(&grid, 0, 3), 6);
+        assert_eq!(
+            min_cost_to_any(&grid, Topology::Bounded, (0, 0), &[(2, 2)], 0, 3),
+            Some(((2, 2), 14))
+        );
+        assert_eq!(
+            min_cost_to_any(&grid, Topology::Bounded, (0, 0), &[(1, 1)], 0, 3),
+            None
+        );
+        assert_eq!(
+            cost_map(&grid, Topology::Bounded, (3, 3), 0, 3)[(1, 2)],
+            None
+        );
+        assert_eq!(
+            cost_map(&grid, Topology::Bounded, (1, 1), 0, 3)[(0, 0)],
+            None
+        );
+    }
+
+    #[test]
+    fn test_cost_map() {
+        let grid = parse_grid(TEST_INPUT);
+        let costs = cost_map(&grid, Topology::Bounded, (0, 0), 4, 10);
+        assert_eq!(costs[(0, 0)], Some(0));
+        assert_eq!(costs[(12, 12)], Some(94));
+        for y in 0..grid.rows() as i32 {
+            for x in 0..grid.cols() as i32 {
+                assert_eq!(
+                    costs[(y as usize, x as usize)],
+                    min_cost_to_any(&grid, Topology::Bounded, (0, 0), &[(x, y)], 4, 10)
+                        .map(|(_, loss)| loss)
+                );
+            }
+        }
+    }
+
+    fn generate_grid(cols: usize, rows: usize, seed: u64) -> Grid<Option<u32>> {
+        let mut state = seed;
+        Grid::from_vec(
+            (0..cols * rows)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    Some((state >> 33) as u32 % 9 + 1)
+                })
+                .collect(),
+            cols,
+        )
+    }
+
+    #[test]
+    fn test_runs() {
+        let grid = parse_grid(TEST_INPUT);
+        let goal = (grid.cols() as i32 - 1, grid.rows() as i32 - 1);
+        assert_eq!(
+            min_cost_runs(&grid, (0, 0), &[goal], 0, 3),
+            Some((goal, 102))
+        );
+        assert_eq!(
+            min_cost_runs(&grid, (0, 0), &[goal], 4, 10),
+            Some((goal, 94))
+        );
+
+        for seed in 0..5 {
+            let grid = generate_grid(30, 20, seed);
+            for (min_span, max_span) in [(0, 3), (4, 10), (2, 5)] {
+                let goals = [(29, 19), (29, 0), (0, 19)];
+                assert_eq!(
+                    min_cost_runs(&grid, (3, 7), &goals, min_span, max_span),
+                    min_cost_to_any(&grid, Topology::Bounded, (3, 7), &goals, min_span, max_span)
+                );
+            }
+        }
+    }
+
+    // cargo test --release -- --ignored --nocapture bench_runs
+    #[test]
+    #[ignore]
+    fn bench_runs() {
+        use std::time::Instant;
+
+        for size in [100, 200, 400] {
+            let grid = generate_grid(size, size, size as u64);
+            let goal = (size as i32 - 1, size as i32 - 1);
+            for (min_span, max_span) in [(0, 3), (4, 10)] {
+                let now = Instant::now();
+                let spans = min_cost_to_any(
+                    &grid,
+                    Topology::Bounded,
+                    (0, 0),
+                    &[goal],
+                    min_span,
+                    max_span,
+                );
+                let spans_time = now.elapsed();
+                let now = Instant::now();
+                let runs = min_cost_runs(&grid, (0, 0), &[goal], min_span, max_span);
+                let runs_time = now.elapsed();
+                assert_eq!(spans, runs);
+                println!(
+                    "{size}x{size} runs of {min_span}..={max_span}: cell by cell {spans_time:?}, whole runs {runs_time:?}"
+                );
+            }
+        }
+    }
+
+    // Losses of every route from `state` that doesn't repeat a state, by depth-first search.
+    fn all_route_losses(
+        grid: &Grid<Option<u32>>,
+        state: State,
+        goal: Position,
+        min_span: u32,
+        max_span: u32,
+        on_path: &mut HashSet<State>,
+    ) -> Vec<u32> {
+        if (state.0, state.1) == goal && state.2.can_stop(min_span) {
+            return vec![0];
+        }
+        let mut losses = Vec::new();
+        for (next, cell_loss) in get_moves(grid, Topology::Bounded, state, min_span, max_span) {
+            if on_path.insert(next) {
+                losses.extend(
+                    all_route_losses(grid, next, goal, min_span, max_span, on_path)
+                        .into_iter()
+                        .map(|loss| loss + cell_loss),
+                );
+                on_path.remove(&next);
+            }
+        }
+        losses
+    }
+
+    #[test]
+    fn test_k_cheapest_routes() {
+        let grid = parse_grid(TEST_INPUT);
+        let goal = (grid.cols() as i32 - 1, grid.rows() as i32 - 1);
+        let routes = k_cheapest_routes(&grid, (0, 0), goal, 10, 0, 3);
+        assert_eq!(routes.len(), 10);
+        assert_eq!(routes[0].loss, 102);
+        assert!(routes.windows(2).all(|w| w[0].loss <= w[1].loss));
+        assert_eq!(
+            routes
+                .iter()
+                .map(|r| &r.cells)
+                .collect::<HashSet<_>>()
+                .len(),
+            10
+        );
+        for route in &routes {
+            assert_eq!(route.cells.first(), Some(&(0, 0)));
+            assert_eq!(route.cells.last(), Some(&goal));
+            assert_eq!(
+                route.cells[1..]
+                    .iter()
+                    .map(|&(x, y)| grid[(y as usize, x as usize)].unwrap())
+                    .sum::<u32>(),
+                route.loss
+            );
+        }
+        assert_eq!(route_loss_histogram(&routes).values().sum::<usize>(), 10);
+        assert_eq!(k_cheapest_routes(&grid, (0, 0), goal, 1, 4, 10)[0].loss, 94);
+    }
+
+    #[test]
+    fn test_k_cheapest_routes_exhaustive() {
+        let grid = parse_grid("1321\n2#19\n3114");
+        for (min_span, max_span) in [(0, 3), (0, 2), (2, 3)] {
+            let start = (0, 0, START_SPAN);
+            let mut losses = all_route_losses(
+                &grid,
+                start,
+                (3, 2),
+                min_span,
+                max_span,
+                &mut HashSet::from([start]),
+            );
+            losses.sort();
+            let routes =
+                k_cheapest_routes(&grid, (0, 0), (3, 2), losses.len() + 5, min_span, max_span);
+            assert_eq!(routes.iter().map(|r| r.loss).collect::<Vec<_>>(), losses);
+        }
+    }
+
+    #[test]
+    fn test_toroidal() {
+        let grid = parse_grid("1991\n9999\n9999");
+        assert_eq!(
+            min_cost_to_any(&grid, Topology::Bounded, (0, 0), &[(3, 0)], 0, 3),
+            Some(((3, 0), 19))
+        );
+        assert_eq!(
+            min_cost_to_any(&grid, Topology::Toroidal, (0, 0), &[(3, 0)], 0, 3),
+            Some(((3, 0), 1))
+        );
+        assert_eq!(
+            cost_map(&grid, Topology::Toroidal, (0, 0), 0, 3)[(2, 0)],
+            Some(9)
+        );
+
+        let grid = parse_grid(TEST_INPUT);
+        let bounded = cost_map(&grid, Topology::Bounded, (0, 0), 4, 10);
+        let toroidal = cost_map(&grid, Topology::Toroidal, (0, 0), 4, 10);
+        assert!(toroidal
+            .iter()
+            .zip(bounded.iter())
+            .all(|(toroidal, bounded)| toroidal.unwrap() <= bounded.unwrap_or(u32::MAX)));
+        assert!(toroidal[(12, 12)].unwrap() < 94);
+    }
+
+    #[test]
+    fn test_tiled() {
+        let grid = parse_grid("1991\n9999\n9999");
+        let tiled = Topology::Tiled {
+            increment: 5,
+            max_tile_distance: 3,
+        };
+        assert_eq!(
+            min_cost_to_any(&grid, tiled, (0, 0), &[(3, 0)], 0, 3),
+            Some(((3, 0), 19))
+        );
+        assert_eq!(
+            min_cost_to_any(&grid, tiled, (0, 0), &[(-1, 0)], 0, 3),
+            Some(((-1, 0), 6))
+        );
+
+        let grid = parse_grid(TEST_INPUT);
+        assert_eq!(
+            min_cost_to_any(
+                &grid,
+                Topology::Tiled {
+                    increment: 100,
+                    max_tile_distance: 1,
+                },
+                (0, 0),
+                &[(12, 12)],
+                4,
+                10
+            ),
+            Some(((12, 12), 94))
+        );
+
+        // The same map written out as 2x2 tiles
+        let mut expanded = Grid::new(26, 26);
+        for y in 0..26 {
+            for x in 0..26 {
+                expanded[(y, x)] = Some(grid[(y % 13, x % 13)].unwrap() + (y / 13 + x / 13) as u32);
+            }
+        }
+        let (_, tiled_loss) = min_cost_to_any(
+            &grid,
+            Topology::Tiled {
+                increment: 1,
+                max_tile_distance: 2,
+            },
+            (0, 0),
+            &[(25, 25)],
+            0,
+            3,
+        )
+        .unwrap();
+        assert!(tiled_loss <= min_cost(&expanded, 0, 3));
+    }
+
+    #[test]
+    fn test_tiled_unreachable() {
+        let tiled = Topology::Tiled {
+            increment: 1,
+            max_tile_distance: 4,
+        };
+        // The goal's cell is an obstacle in every tile
+        let grid = parse_grid("11\n1#");
+        assert_eq!(min_cost_to_any(&grid, tiled, (0, 0), &[(5, 5)], 0, 3), None);
+
+        // Walled off in every tile, so only the bound ends the search
+        let grid = parse_grid("1#1\n###\n1#1");
+        assert_eq!(min_cost_to_any(&grid, tiled, (0, 0), &[(2, 0)], 0, 3), None);
+        assert_eq!(
+            min_cost_to_any(&grid, tiled, (0, 0), &[(0, 0)], 0, 3),
+            Some(((0, 0), 0))
+        );
+
+        // Reachable, but only past the bound
+        let grid = parse_grid("19\n99");
+        assert_eq!(
+            min_cost_to_any(&grid, tiled, (0, 0), &[(10, 0)], 0, 3),
+            None
+        );
+        assert!(min_cost_to_any(&grid, tiled, (0, 0), &[(9, 0)], 0, 3).is_some());
+    }
 }
@@ -1,8 +1,9 @@
-use std::fs;
+use std::{collections::HashMap, fmt::Display, fs, str::FromStr, thread, time::Duration};
 
+use colored::Colorize;
 use grid::Grid;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Direction {
     Up,
     Down,
@@ -10,17 +11,21 @@ enum Direction {
     Right,
 }
 impl Direction {
-    fn opposite(self) -> Direction {
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    fn get_offset(self) -> (isize, isize) {
         match self {
-            Direction::Up => Direction::Down,
-            Direction::Down => Direction::Up,
-            Direction::Left => Direction::Right,
-            Direction::Right => Direction::Left,
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
         }
-    }    
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 struct Beam {
     direction: Direction,
     position: (isize, isize),
@@ -35,16 +40,33 @@ impl Beam {
     }
 
     fn go(&self, direction: Direction) -> Beam {
-        match direction {
-            Direction::Up => Beam::new(Direction::Up, (self.position.0, self.position.1 - 1)),
-            Direction::Down => Beam::new(Direction::Down, (self.position.0, self.position.1 + 1)),
-            Direction::Left => Beam::new(Direction::Left, (self.position.0 - 1, self.position.1)),
-            Direction::Right => Beam::new(Direction::Right, (self.position.0 + 1, self.position.1)),
-        }
+        let (x_offset, y_offset) = direction.get_offset();
+        Beam::new(
+            direction,
+            (self.position.0 + x_offset, self.position.1 + y_offset),
+        )
     }
+}
+
+// "x,y,d" with d one of ^, v, < or >
+impl FromStr for Beam {
+    type Err = ();
 
-    fn go_straight(&self) -> Beam {
-        self.go(self.direction)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+        let x = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let y = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let direction = match parts.next().ok_or(())? {
+            "^" => Direction::Up,
+            "v" => Direction::Down,
+            "<" => Direction::Left,
+            ">" => Direction::Right,
+            _ => return Err(()),
+        };
+        if parts.next().is_some() {
+            return Err(());
+        }
+        Ok(Beam::new(direction, (x, y)))
     }
 }
 
@@ -55,63 +77,126 @@ enum TileKind {
     BackwardMirror,
     VerticalSplitter,
     HorizontalSplitter,
+    Absorber,
+    // Only lets through beams going in its direction, absorbing the rest
+    Diode(Direction),
+    // Sends the beam out of the other portal with the same label, keeping its direction
+    Portal {
+        label: char,
+        partner: Option<(isize, isize)>,
+    },
+}
+
+impl TileKind {
+    fn get_exit_directions(self, direction: Direction) -> Vec<Direction> {
+        match self {
+            TileKind::Open | TileKind::Portal { .. } => vec![direction],
+            TileKind::ForwardMirror => match direction {
+                Direction::Up => vec![Direction::Right],
+                Direction::Down => vec![Direction::Left],
+                Direction::Left => vec![Direction::Down],
+                Direction::Right => vec![Direction::Up],
+            },
+            TileKind::BackwardMirror => match direction {
+                Direction::Up => vec![Direction::Left],
+                Direction::Down => vec![Direction::Right],
+                Direction::Left => vec![Direction::Up],
+                Direction::Right => vec![Direction::Down],
+            },
+            TileKind::VerticalSplitter => match direction {
+                Direction::Up | Direction::Down => vec![direction],
+                Direction::Left | Direction::Right => vec![Direction::Up, Direction::Down],
+            },
+            TileKind::HorizontalSplitter => match direction {
+                Direction::Up | Direction::Down => vec![Direction::Left, Direction::Right],
+                Direction::Left | Direction::Right => vec![direction],
+            },
+            TileKind::Absorber => vec![],
+            TileKind::Diode(allowed) if allowed == direction => vec![direction],
+            TileKind::Diode(_) => vec![],
+        }
+    }
+
+    // Where a beam passing through a tile of this kind leaves from
+    fn get_exit_position(self, position: (isize, isize)) -> (isize, isize) {
+        match self {
+            TileKind::Portal { label, partner } => {
+                partner.unwrap_or_else(|| panic!("Portal {label} has no partner"))
+            }
+            _ => position,
+        }
+    }
+
+    fn get_exits(self, beam: &Beam) -> Vec<Beam> {
+        let from = Beam::new(beam.direction, self.get_exit_position(beam.position));
+        self.get_exit_directions(beam.direction)
+            .into_iter()
+            .map(|direction| from.go(direction))
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
 struct Tile {
     kind: TileKind,
-    entry_directions: Vec<Direction>,
+    // Bitset of `Direction::bit`s the tile was entered with
+    entry_directions: u8,
+    // A beam came out of this portal from its partner
+    teleported_to: bool,
 }
 
 impl Tile {
     fn new(kind: TileKind) -> Self {
         Tile {
             kind,
-            entry_directions: Vec::new(),
+            entry_directions: 0,
+            teleported_to: false,
         }
     }
 
     fn energized(&self) -> bool {
-        !self.entry_directions.is_empty()
+        self.entry_directions != 0 || self.teleported_to
+    }
+
+    fn entered(&self, direction: Direction) -> bool {
+        self.entry_directions & direction.bit() != 0
     }
 
+    // A beam's path only depends on the tile and direction it enters with, so that's all that
+    // needs remembering for the simulation to end, whatever the tile does with the beam.
     fn visited(&self, direction: Direction) -> bool {
-        match self.kind {
-            TileKind::Open => self.entry_directions.contains(&direction) || self.entry_directions.contains(&direction.opposite()),
-            TileKind::HorizontalSplitter | TileKind::VerticalSplitter => self.energized(),
-            _ => self.entry_directions.contains(&direction),
-        }
+        self.entered(direction)
     }
 
     fn visit(&mut self, beam: &Beam) -> Vec<Beam> {
-        self.entry_directions.push(beam.direction);
-        match self.kind {
-            TileKind::Open => vec![beam.go_straight()],
-            TileKind::ForwardMirror => match beam.direction {
-                Direction::Up => vec![beam.go(Direction::Right)],
-                Direction::Down => vec![beam.go(Direction::Left)],
-                Direction::Left => vec![beam.go(Direction::Down)],
-                Direction::Right => vec![beam.go(Direction::Up)],
-            },
-            TileKind::BackwardMirror => match beam.direction {
-                Direction::Up => vec![beam.go(Direction::Left)],
-                Direction::Down => vec![beam.go(Direction::Right)],
-                Direction::Left => vec![beam.go(Direction::Up)],
-                Direction::Right => vec![beam.go(Direction::Down)],
-            },
-            TileKind::VerticalSplitter => match beam.direction {
-                Direction::Up | Direction::Down => vec![beam.go_straight()],
-                Direction::Left | Direction::Right => {
-                    vec![beam.go(Direction::Up), beam.go(Direction::Down)]
-                }
-            },
-            TileKind::HorizontalSplitter => match beam.direction {
-                Direction::Up | Direction::Down => {
-                    vec![beam.go(Direction::Left), beam.go(Direction::Right)]
-                }
-                Direction::Left | Direction::Right => vec![beam.go_straight()],
+        self.entry_directions |= beam.direction.bit();
+        self.kind.get_exits(beam)
+    }
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self.kind {
+            TileKind::Open => match self.entry_directions.count_ones() {
+                0 => '.',
+                1 if self.entered(Direction::Up) => '^',
+                1 if self.entered(Direction::Down) => 'v',
+                1 if self.entered(Direction::Left) => '<',
+                1 => '>',
+                count => char::from_digit(count, 10).unwrap(),
             },
-        }
+            TileKind::ForwardMirror => '/',
+            TileKind::BackwardMirror => '\\',
+            TileKind::VerticalSplitter => '|',
+            TileKind::HorizontalSplitter => '-',
+            TileKind::Absorber => '#',
+            TileKind::Diode(Direction::Up) => '↑',
+            TileKind::Diode(Direction::Down) => '↓',
+            TileKind::Diode(Direction::Left) => '←',
+            TileKind::Diode(Direction::Right) => '→',
+            TileKind::Portal { label, .. } => label,
+        };
+        write!(f, "{c}")
     }
 }
 
@@ -123,78 +208,515 @@ impl From<char> for Tile {
             '\\' => Tile::new(TileKind::BackwardMirror),
             '|' => Tile::new(TileKind::VerticalSplitter),
             '-' => Tile::new(TileKind::HorizontalSplitter),
+            '#' => Tile::new(TileKind::Absorber),
+            '↑' => Tile::new(TileKind::Diode(Direction::Up)),
+            '↓' => Tile::new(TileKind::Diode(Direction::Down)),
+            '←' => Tile::new(TileKind::Diode(Direction::Left)),
+            '→' => Tile::new(TileKind::Diode(Direction::Right)),
+            'A'..='Z' => Tile::new(TileKind::Portal {
+                label: c,
+                partner: None,
+            }),
             _ => panic!("Invalid tile {c}"),
         }
     }
 }
 
-fn get_energy_level_with_starting_beam(mut grid: Grid<Tile>, starting_beam: Beam) -> usize {
-    let mut beams = vec![starting_beam];
+fn parse_grid(input: &str) -> Grid<Tile> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let mut grid = Grid::from_vec(
+        lines
+            .iter()
+            .flat_map(|l| l.chars().map(Tile::from))
+            .collect::<Vec<_>>(),
+        lines[0].chars().count(),
+    );
+    link_portals(&mut grid);
+    grid
+}
+
+fn link_portals(grid: &mut Grid<Tile>) {
+    let mut portals: HashMap<char, Vec<(isize, isize)>> = HashMap::new();
+    for ((y, x), tile) in grid.indexed_iter() {
+        if let TileKind::Portal { label, .. } = tile.kind {
+            portals
+                .entry(label)
+                .or_default()
+                .push((x as isize, y as isize));
+        }
+    }
+    for (label, positions) in portals {
+        let [first, second] = positions[..] else {
+            panic!("Portal {label} appears {} times", positions.len());
+        };
+        for (position, partner) in [(first, second), (second, first)] {
+            grid[(position.1 as usize, position.0 as usize)].kind = TileKind::Portal {
+                label,
+                partner: Some(partner),
+            };
+        }
+    }
+}
+
+fn reset(grid: &mut Grid<Tile>) {
+    grid.iter_mut().for_each(|tile| {
+        tile.entry_directions = 0;
+        tile.teleported_to = false;
+    });
+}
+
+// Moves the beam through its tile, returning the beams leaving it.
+fn advance(grid: &mut Grid<Tile>, beam: &Beam) -> Vec<Beam> {
+    if beam.position.0 < 0 || beam.position.1 < 0 {
+        return Vec::new();
+    }
+    let Some(tile) = grid.get_mut(beam.position.1 as usize, beam.position.0 as usize) else {
+        return Vec::new();
+    };
+    if tile.visited(beam.direction) {
+        return Vec::new();
+    }
+    let exits = tile.visit(beam);
+    if let TileKind::Portal {
+        partner: Some((x, y)),
+        ..
+    } = tile.kind
+    {
+        grid[(y as usize, x as usize)].teleported_to = true;
+    }
+    exits
+}
+
+fn get_energy_level_with_starting_beam(grid: &mut Grid<Tile>, starting_beam: Beam) -> usize {
+    get_energy_level_with_starting_beams(grid, &[starting_beam])
+}
+
+// The grid doubles as scratch state, so it's cleared before use and can be reused between beams.
+fn get_energy_level_with_starting_beams(grid: &mut Grid<Tile>, starting_beams: &[Beam]) -> usize {
+    reset(grid);
+    let mut beams = starting_beams.to_vec();
 
     while let Some(beam) = beams.pop() {
-        if beam.position.0 < 0 || beam.position.1 < 0 {
-            continue;
+        beams.extend(advance(grid, &beam));
+    }
+
+    grid.iter().filter(|t| t.energized()).count()
+}
+
+// For every tile, the indices of the starting beams that energize it. Beams don't affect each
+// other, so each one is simulated on its own.
+fn get_beam_attribution(grid: &mut Grid<Tile>, starting_beams: &[Beam]) -> Grid<Vec<usize>> {
+    let mut attribution: Grid<Vec<usize>> = Grid::new(grid.rows(), grid.cols());
+    for (i, beam) in starting_beams.iter().enumerate() {
+        get_energy_level_with_starting_beam(grid, *beam);
+        for (tile, beams) in grid.iter().zip(attribution.iter_mut()) {
+            if tile.energized() {
+                beams.push(i);
+            }
         }
-        if let Some(tile) = grid.get_mut(beam.position.1 as usize, beam.position.0 as usize) {
-            if tile.visited(beam.direction) {
-                continue;
+    }
+    attribution
+}
+
+fn get_tiles_lit_only_by(attribution: &Grid<Vec<usize>>, beam: usize) -> Vec<(isize, isize)> {
+    attribution
+        .indexed_iter()
+        .filter(|(_, beams)| beams[..] == [beam])
+        .map(|((y, x), _)| (x as isize, y as isize))
+        .collect()
+}
+
+// Mirrors and splitters as in the input, open tiles the beam passed through as the direction it
+// went, or the number of directions where beams cross. Energized tiles are highlighted in colour.
+fn render(grid: &Grid<Tile>, highlight: bool) -> String {
+    let mut output = String::new();
+    for row in grid.iter_rows() {
+        for tile in row {
+            if highlight && tile.energized() {
+                output += &tile.to_string().black().on_yellow().to_string();
+            } else {
+                output += &tile.to_string();
             }
-            beams.extend(tile.visit(&beam));
         }
+        output.push('\n');
     }
+    output
+}
 
-    grid.iter().filter(|t| t.energized()).count()
+// Successive frames of the beams spreading out from `starting_beam`, one tile per frame.
+fn get_frames(grid: &mut Grid<Tile>, starting_beam: Beam, highlight: bool) -> Vec<String> {
+    reset(grid);
+    let mut frames = Vec::new();
+    let mut beams = vec![starting_beam];
+
+    while !beams.is_empty() {
+        beams = beams.iter().flat_map(|beam| advance(grid, beam)).collect();
+        frames.push(render(grid, highlight));
+    }
+
+    frames
 }
 
-fn part_one(input: &str) -> usize {
-    let lines = input.lines().collect::<Vec<_>>();
-    let grid = Grid::from_vec(
-        lines
-            .iter()
-            .flat_map(|l| l.chars().map(Tile::from))
-            .collect::<Vec<_>>(),
-        lines[0].len(),
-    );
+fn animate(input: &str, frame_delay: Duration) {
+    let mut grid = parse_grid(input);
+    for frame in get_frames(&mut grid, Beam::new(Direction::Right, (0, 0)), true) {
+        print!("\x1b[2J\x1b[H{frame}");
+        thread::sleep(frame_delay);
+    }
+}
 
-    get_energy_level_with_starting_beam(grid, Beam::new(Direction::Right, (0, 0)))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Objective {
+    Maximise,
+    Minimise,
 }
 
-fn part_two(input: &str) -> usize {
-    let lines = input.lines().collect::<Vec<_>>();
-    let grid = Grid::from_vec(
-        lines
-            .iter()
-            .flat_map(|l| l.chars().map(Tile::from))
-            .collect::<Vec<_>>(),
-        lines[0].len(),
-    );
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Placement {
+    position: (isize, isize),
+    kind: TileKind,
+    energy: usize,
+}
 
-    let mut max_energy = 0;
+// Best single mirror or splitter to add on an open tile. A tile the beam never reaches can't
+// change anything, so only the open tiles energized without the new element are simulated again.
+fn find_best_placement(
+    grid: &mut Grid<Tile>,
+    starting_beam: Beam,
+    objective: Objective,
+) -> Option<Placement> {
+    let base_energy = get_energy_level_with_starting_beam(grid, starting_beam);
+    let mut reached = Vec::new();
+    let mut unreached = None;
+    for ((y, x), tile) in grid.indexed_iter() {
+        if tile.kind != TileKind::Open {
+            continue;
+        }
+        if tile.energized() {
+            reached.push((y, x));
+        } else if unreached.is_none() {
+            unreached = Some((y, x));
+        }
+    }
+
+    let mut candidates = unreached
+        .map(|(y, x)| Placement {
+            position: (x as isize, y as isize),
+            kind: TileKind::ForwardMirror,
+            energy: base_energy,
+        })
+        .into_iter()
+        .collect::<Vec<_>>();
+    for (y, x) in reached {
+        for kind in [
+            TileKind::ForwardMirror,
+            TileKind::BackwardMirror,
+            TileKind::VerticalSplitter,
+            TileKind::HorizontalSplitter,
+        ] {
+            grid[(y, x)].kind = kind;
+            candidates.push(Placement {
+                position: (x as isize, y as isize),
+                kind,
+                energy: get_energy_level_with_starting_beam(grid, starting_beam),
+            });
+        }
+        grid[(y, x)].kind = TileKind::Open;
+    }
+
+    // Reversed so that ties go to the first candidate
+    match objective {
+        Objective::Maximise => candidates.into_iter().rev().max_by_key(|p| p.energy),
+        Objective::Minimise => candidates.into_iter().rev().min_by_key(|p| p.energy),
+    }
+}
+
+fn get_edge_beams(grid: &Grid<Tile>) -> Vec<Beam> {
+    let mut beams = Vec::new();
     for i in 0..grid.rows() {
-        max_energy = max_energy.max(get_energy_level_with_starting_beam(
-            grid.clone(),
-            Beam::new(Direction::Right, (0, i as isize)),
-        ));
-        max_energy = max_energy.max(get_energy_level_with_starting_beam(
-            grid.clone(),
-            Beam::new(Direction::Left, (grid.cols() as isize - 1, i as isize)),
+        beams.push(Beam::new(Direction::Right, (0, i as isize)));
+        beams.push(Beam::new(
+            Direction::Left,
+            (grid.cols() as isize - 1, i as isize),
         ));
     }
     for i in 0..grid.cols() {
-        max_energy = max_energy.max(get_energy_level_with_starting_beam(
-            grid.clone(),
-            Beam::new(Direction::Down, (i as isize, 0)),
-        ));
-        max_energy = max_energy.max(get_energy_level_with_starting_beam(
-            grid.clone(),
-            Beam::new(Direction::Up, (i as isize, grid.rows() as isize - 1)),
+        beams.push(Beam::new(Direction::Down, (i as isize, 0)));
+        beams.push(Beam::new(
+            Direction::Up,
+            (i as isize, grid.rows() as isize - 1),
         ));
     }
-    max_energy
+    beams
+}
+
+#[derive(Debug, Clone)]
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        BitSet {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    fn union_with(&mut self, other: &BitSet) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+// A beam is deterministic until it hits a splitter side-on, so the contraption is split into
+// segments: straight-line runs (through mirrors) that start at an edge or a splitter exit and end
+// by leaving the grid, looping back on themselves or splitting into two further segments.
+// Segments that feed into each other are collapsed into strongly connected components, and each
+// component keeps the union of every tile reachable from it, shared by all beams that reach it.
+struct BeamGraph {
+    cols: usize,
+    rows: usize,
+    // (tile, direction) that starts a segment -> segment
+    segment_ids: HashMap<(usize, Direction), usize>,
+    // Segment -> component
+    components: Vec<usize>,
+    // Component -> tiles energized by any beam reaching it
+    reachable: Vec<BitSet>,
+}
+
+impl BeamGraph {
+    fn new(grid: &Grid<Tile>) -> Self {
+        let (cols, rows) = (grid.cols(), grid.rows());
+        let kinds = grid.iter().map(|tile| tile.kind).collect::<Vec<_>>();
+
+        let mut pending = get_edge_beams(grid)
+            .into_iter()
+            .map(|beam| {
+                (
+                    beam.position.1 as usize * cols + beam.position.0 as usize,
+                    beam.direction,
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut segment_ids = HashMap::new();
+        let mut tiles = Vec::new();
+        let mut successors = Vec::new();
+        // Generation-stamped (tile, direction) scratch, so loop detection never needs clearing
+        let mut seen = vec![usize::MAX; cols * rows * 4];
+
+        while let Some(head) = pending.pop() {
+            if segment_ids.contains_key(&head) {
+                continue;
+            }
+            let id = tiles.len();
+            segment_ids.insert(head, id);
+            let mut segment_tiles = BitSet::new(cols * rows);
+            let mut exits = Vec::new();
+            let (mut tile, mut direction) = head;
+            while seen[tile * 4 + direction as usize] != id {
+                seen[tile * 4 + direction as usize] = id;
+                segment_tiles.insert(tile);
+                let beam = Beam::new(direction, ((tile % cols) as isize, (tile / cols) as isize));
+                let (x, y) = kinds[tile].get_exit_position(beam.position);
+                segment_tiles.insert(y as usize * cols + x as usize);
+                let beams = kinds[tile].get_exits(&beam);
+                let splits = beams.len() > 1;
+                let neighbours = beams
+                    .into_iter()
+                    .filter(
+                        |Beam {
+                             position: (x, y), ..
+                         }| {
+                            *x >= 0 && *y >= 0 && (*x as usize) < cols && (*y as usize) < rows
+                        },
+                    )
+                    .map(|beam| {
+                        let (x, y) = beam.position;
+                        (y as usize * cols + x as usize, beam.direction)
+                    })
+                    .collect::<Vec<_>>();
+                if splits {
+                    exits = neighbours;
+                    break;
+                }
+                let Some(&next) = neighbours.first() else {
+                    break;
+                };
+                (tile, direction) = next;
+            }
+            tiles.push(segment_tiles);
+            successors.push(exits.clone());
+            pending.extend(exits);
+        }
+        let successors = successors
+            .into_iter()
+            .map(|exits| {
+                exits
+                    .iter()
+                    .map(|head| segment_ids[head])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let (components, component_count) = strongly_connected_components(&successors);
+        // Tarjan numbers components successors first, so every successor's set is already done
+        let mut reachable = vec![BitSet::new(cols * rows); component_count];
+        let mut members = vec![Vec::new(); component_count];
+        for (segment, &component) in components.iter().enumerate() {
+            members[component].push(segment);
+        }
+        for component in 0..component_count {
+            let mut set = BitSet::new(cols * rows);
+            for &segment in &members[component] {
+                set.union_with(&tiles[segment]);
+                for &successor in &successors[segment] {
+                    if components[successor] != component {
+                        set.union_with(&reachable[components[successor]]);
+                    }
+                }
+            }
+            reachable[component] = set;
+        }
+
+        BeamGraph {
+            cols,
+            rows,
+            segment_ids,
+            components,
+            reachable,
+        }
+    }
+
+    // Only beams entering from an edge of the contraption are known
+    fn get_energy_level(&self, starting_beam: Beam) -> usize {
+        let (x, y) = starting_beam.position;
+        if x < 0 || y < 0 || x as usize >= self.cols || y as usize >= self.rows {
+            return 0;
+        }
+        let head = (y as usize * self.cols + x as usize, starting_beam.direction);
+        let segment = self.segment_ids[&head];
+        self.reachable[self.components[segment]].len()
+    }
+}
+
+// Tarjan's algorithm, iteratively. Returns the component of every node and the component count,
+// components numbered so that successors come before predecessors.
+fn strongly_connected_components(successors: &[Vec<usize>]) -> (Vec<usize>, usize) {
+    let n = successors.len();
+    let mut index = vec![usize::MAX; n];
+    let mut low_link = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = vec![usize::MAX; n];
+    let mut component_count = 0;
+    let mut next_index = 0;
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        // (node, next successor to look at)
+        let mut call_stack = vec![(root, 0)];
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (node, ref mut next_successor)) = call_stack.last_mut() {
+            if let Some(&successor) = successors[node].get(*next_successor) {
+                *next_successor += 1;
+                if index[successor] == usize::MAX {
+                    index[successor] = next_index;
+                    low_link[successor] = next_index;
+                    next_index += 1;
+                    stack.push(successor);
+                    on_stack[successor] = true;
+                    call_stack.push((successor, 0));
+                } else if on_stack[successor] {
+                    low_link[node] = low_link[node].min(index[successor]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if low_link[node] == index[node] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    components[member] = component_count;
+                    if member == node {
+                        break;
+                    }
+                }
+                component_count += 1;
+            }
+        }
+    }
+
+    (components, component_count)
+}
+
+fn part_one(input: &str) -> usize {
+    let mut grid = parse_grid(input);
+
+    get_energy_level_with_starting_beam(&mut grid, Beam::new(Direction::Right, (0, 0)))
+}
+
+fn part_two(input: &str) -> usize {
+    let grid = parse_grid(input);
+    let graph = BeamGraph::new(&grid);
+
+    get_edge_beams(&grid)
+        .into_iter()
+        .map(|beam| graph.get_energy_level(beam))
+        .max()
+        .unwrap()
 }
 
 fn main() {
     let input = fs::read_to_string("input.txt").expect("Error reading input.txt");
+    if std::env::args().any(|arg| arg == "--animate") {
+        animate(&input, Duration::from_millis(50));
+    }
+    if std::env::args().any(|arg| arg == "--place") {
+        let mut grid = parse_grid(&input);
+        for objective in [Objective::Maximise, Objective::Minimise] {
+            if let Some(placement) =
+                find_best_placement(&mut grid, Beam::new(Direction::Right, (0, 0)), objective)
+            {
+                println!("{objective:?}: {placement:?}");
+            }
+        }
+    }
+    if let Some(i) = std::env::args().position(|arg| arg == "--attribute") {
+        let beams = std::env::args()
+            .skip(i + 1)
+            .map(|arg| {
+                arg.parse::<Beam>()
+                    .expect("Beams are given as x,y,direction")
+            })
+            .collect::<Vec<_>>();
+        let mut grid = parse_grid(&input);
+        let attribution = get_beam_attribution(&mut grid, &beams);
+        for (i, beam) in beams.iter().enumerate() {
+            println!(
+                "{beam:?}: {} tiles lit only by this beam",
+                get_tiles_lit_only_by(&attribution, i).len()
+            );
+        }
+    }
     println!("Part one: {}", part_one(&input));
     println!("Part two: {}", part_two(&input));
 }
@@ -223,4 +745,245 @@ mod tests {
     fn test_part_two() {
         assert_eq!(part_two(TEST_INPUT), 51);
     }
+
+    fn generate_input(size: usize, seed: u64, extras: bool) -> String {
+        let mut state = seed;
+        let mut input = Vec::new();
+        for _ in 0..size {
+            for _ in 0..size {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                input.push(match (state >> 33) % 20 {
+                    0 => '/',
+                    1 => '\\',
+                    2 => '|',
+                    3 => '-',
+                    4 if extras => '#',
+                    5 if extras => ['↑', '↓', '←', '→'][(state >> 40) as usize % 4],
+                    _ => '.',
+                });
+            }
+            input.push('\n');
+        }
+        if extras {
+            for (i, label) in ('A'..='F').enumerate() {
+                for position in [i * 7 + 3, size * size - i * 11 - 5] {
+                    input[position + position / size] = label;
+                }
+            }
+        }
+        input.into_iter().collect()
+    }
+
+    #[test]
+    fn test_beam_graph() {
+        for (size, seed, extras) in [
+            (10, 1, false),
+            (17, 2, false),
+            (30, 3, false),
+            (40, 4, false),
+            (20, 5, true),
+            (30, 6, true),
+            (40, 7, true),
+        ] {
+            let input = generate_input(size, seed, extras);
+            let mut grid = parse_grid(&input);
+            let graph = BeamGraph::new(&grid);
+            for beam in get_edge_beams(&grid) {
+                assert_eq!(
+                    graph.get_energy_level(beam),
+                    get_energy_level_with_starting_beam(&mut grid, beam)
+                );
+            }
+        }
+    }
+
+    // cargo test --release -- --ignored --nocapture bench_part_two
+    #[test]
+    #[ignore]
+    fn bench_part_two() {
+        use std::time::Instant;
+
+        for size in [110, 200, 400] {
+            let input = generate_input(size, size as u64, false);
+            let now = Instant::now();
+            let mut grid = parse_grid(&input);
+            let naive = get_edge_beams(&grid)
+                .into_iter()
+                .map(|beam| get_energy_level_with_starting_beam(&mut grid, beam))
+                .max()
+                .unwrap();
+            let naive_time = now.elapsed();
+            let now = Instant::now();
+            let graph = part_two(&input);
+            let graph_time = now.elapsed();
+            assert_eq!(naive, graph);
+            println!("{size}x{size}: beam by beam {naive_time:?}, beam graph {graph_time:?}");
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut grid = parse_grid(TEST_INPUT);
+        get_energy_level_with_starting_beam(&mut grid, Beam::new(Direction::Right, (0, 0)));
+        assert_eq!(
+            render(&grid, false),
+            r#">|<<<\....
+|v-.\^....
+.v...|->>>
+.v...v^.|.
+.v...v^...
+.v...v^..\
+.v../2\\..
+<->-/vv|..
+.|<<<2-|.\
+.v//.|.v..
+"#
+        );
+
+        // Diodes keep their own glyphs, so they can't be mistaken for lit open tiles
+        let mut grid = parse_grid("..→..\n.....\n..↑..");
+        get_energy_level_with_starting_beam(&mut grid, Beam::new(Direction::Right, (0, 0)));
+        assert_eq!(render(&grid, false), ">>→>>\n.....\n..↑..\n");
+        let mut grid = parse_grid("..\\..\n.....\n..↓..\n..↑..");
+        get_energy_level_with_starting_beam(&mut grid, Beam::new(Direction::Right, (0, 0)));
+        assert_eq!(render(&grid, false), ">>\\..\n..v..\n..↓..\n..↑..\n");
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut grid = parse_grid(TEST_INPUT);
+        let frames = get_frames(&mut grid, Beam::new(Direction::Right, (0, 0)), false);
+        assert_eq!(frames[0].lines().next(), Some(r#">|...\...."#));
+        assert_eq!(frames[1].lines().nth(1), Some(r#"|.-.\....."#));
+        assert_eq!(frames[2].lines().nth(1), Some(r#"|v-.\....."#));
+        assert_eq!(frames.last(), Some(&render(&grid, false)));
+        assert_eq!(grid.iter().filter(|t| t.energized()).count(), 46);
+    }
+
+    fn energy_from_left(input: &str) -> usize {
+        let mut grid = parse_grid(input);
+        get_energy_level_with_starting_beam(&mut grid, Beam::new(Direction::Right, (0, 0)))
+    }
+
+    #[test]
+    fn test_absorber() {
+        assert_eq!(energy_from_left("..#.."), 3);
+        assert_eq!(energy_from_left("..\\..\n.....\n..#.."), 5);
+    }
+
+    #[test]
+    fn test_diode() {
+        assert_eq!(energy_from_left("..→.."), 5);
+        assert_eq!(energy_from_left("..←.."), 3);
+        assert_eq!(energy_from_left("..↑..\n....."), 3);
+        assert_eq!(energy_from_left("..\\..\n.....\n..↓..\n..↑.."), 6);
+    }
+
+    #[test]
+    fn test_portals() {
+        assert_eq!(energy_from_left(".A...\n.....\n...A."), 4);
+        assert_eq!(energy_from_left(".A...\n#....\n...A\\\n....."), 5);
+        assert_eq!(energy_from_left(".\\..A\n.....\n.A..\\"), 7);
+        let grid = parse_grid(".A..B\n\\.B.A");
+        assert_eq!(
+            grid[(0, 1)].kind,
+            TileKind::Portal {
+                label: 'A',
+                partner: Some((4, 1))
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Portal A appears 3 times")]
+    fn test_unpaired_portal() {
+        parse_grid("A.A\n.A.");
+    }
+
+    #[test]
+    fn test_find_best_placement() {
+        let mut grid = parse_grid(TEST_INPUT);
+        let beam = Beam::new(Direction::Right, (0, 0));
+        for objective in [Objective::Maximise, Objective::Minimise] {
+            let best = find_best_placement(&mut grid, beam, objective).unwrap();
+
+            let mut energies = Vec::new();
+            for y in 0..grid.rows() {
+                for x in 0..grid.cols() {
+                    if grid[(y, x)].kind != TileKind::Open {
+                        continue;
+                    }
+                    for kind in [
+                        TileKind::ForwardMirror,
+                        TileKind::BackwardMirror,
+                        TileKind::VerticalSplitter,
+                        TileKind::HorizontalSplitter,
+                    ] {
+                        let mut placed = grid.clone();
+                        placed[(y, x)].kind = kind;
+                        energies.push(get_energy_level_with_starting_beam(&mut placed, beam));
+                    }
+                }
+            }
+            let expected = match objective {
+                Objective::Maximise => energies.iter().max(),
+                Objective::Minimise => energies.iter().min(),
+            };
+            assert_eq!(Some(&best.energy), expected);
+
+            let (x, y) = best.position;
+            assert_eq!(grid[(y as usize, x as usize)].kind, TileKind::Open);
+            grid[(y as usize, x as usize)].kind = best.kind;
+            assert_eq!(
+                get_energy_level_with_starting_beam(&mut grid, beam),
+                best.energy
+            );
+            grid[(y as usize, x as usize)].kind = TileKind::Open;
+        }
+
+        assert_eq!(
+            find_best_placement(&mut parse_grid("/|\\"), beam, Objective::Maximise),
+            None
+        );
+    }
+
+    #[test]
+    fn test_multiple_beams() {
+        let mut grid = parse_grid(TEST_INPUT);
+        let beams = [
+            Beam::new(Direction::Right, (0, 0)),
+            Beam::new(Direction::Left, (4, 4)),
+            Beam::new(Direction::Up, (7, 9)),
+        ];
+        let attribution = get_beam_attribution(&mut grid, &beams);
+        assert_eq!(
+            get_energy_level_with_starting_beams(&mut grid, &beams),
+            attribution.iter().filter(|b| !b.is_empty()).count()
+        );
+        for (i, beam) in beams.iter().enumerate() {
+            assert_eq!(
+                get_energy_level_with_starting_beam(&mut grid, *beam),
+                attribution.iter().filter(|b| b.contains(&i)).count()
+            );
+        }
+
+        // Going left along the empty 5th row, crossing the other beams in the 2nd column
+        assert_eq!(
+            get_tiles_lit_only_by(&attribution, 1),
+            [(0, 4), (2, 4), (3, 4), (4, 4)]
+        );
+        assert_eq!(attribution[(0, 0)], [0]);
+        assert_eq!(attribution[(4, 1)], [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_parse_beam() {
+        let beam = "3,-1,v".parse::<Beam>().unwrap();
+        assert_eq!((beam.position, beam.direction), ((3, -1), Direction::Down));
+        assert!("3,1".parse::<Beam>().is_err());
+        assert!("3,1,x".parse::<Beam>().is_err());
+        assert!("3,1,<,2".parse::<Beam>().is_err());
+    }
 }
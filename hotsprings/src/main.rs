@@ -1,99 +1,545 @@
-use std::{fs, iter::repeat, collections::HashMap};
+use std::{fmt::Display, fs, iter::repeat_n};
 
+type Table = Vec<Vec<Vec<u128>>>;
 
-fn count_options(records: &str, correct_counts: &[usize], cache: &mut HashMap<(usize, usize), usize>) -> usize {
-    let cache_key = (records.len(), correct_counts.len());
-    if let Some(val) = cache.get(&cache_key) {
-        return *val;
+// The state after one more spring, if it doesn't break the groups
+fn next_state(
+    correct_counts: &[usize],
+    group: usize,
+    run: usize,
+    spring: char,
+) -> Option<(usize, usize)> {
+    match spring {
+        '#' if group < correct_counts.len() && run < correct_counts[group] => {
+            Some((group, run + 1))
+        }
+        '.' if run == 0 => Some((group, 0)),
+        '.' if group < correct_counts.len() && run == correct_counts[group] => Some((group + 1, 0)),
+        _ => None,
     }
-    if correct_counts.is_empty() {
-        return if records.chars().all(|c| c != '#') { 1 } else { 0 };
+}
+
+// The springs a record could stand for
+fn get_springs(record: char) -> &'static [char] {
+    match record {
+        '#' => &['#'],
+        '.' => &['.'],
+        _ => &['#', '.'],
+    }
+}
+
+// Ways to reach each state after the first `i` records, as table[i][group][run]: `group` groups
+// of damaged springs are complete and the current run of damaged springs is `run` long.
+fn get_forward_table(records: &str, correct_counts: &[usize]) -> Table {
+    let max_run = correct_counts.iter().max().copied().unwrap_or(0);
+    let empty = vec![vec![0; max_run + 1]; correct_counts.len() + 1];
+    let mut table = vec![empty.clone()];
+    table[0][0][0] = 1;
+
+    for c in records.chars() {
+        let current = table.last().unwrap();
+        let mut next = empty.clone();
+        for (group, runs) in current.iter().enumerate() {
+            for (run, &ways) in runs.iter().enumerate() {
+                if ways == 0 {
+                    continue;
+                }
+                for &spring in get_springs(c) {
+                    if let Some((group, run)) = next_state(correct_counts, group, run, spring) {
+                        next[group][run] += ways;
+                    }
+                }
+            }
+        }
+        table.push(next);
     }
-    let needed_records = correct_counts.iter().sum::<usize>() + correct_counts.len() - 1;
-    if records.len() < needed_records {
-        return 0;
+
+    table
+}
+
+// Whether the records can end in the given state
+fn is_final_state(correct_counts: &[usize], group: usize, run: usize) -> bool {
+    (group == correct_counts.len() && run == 0)
+        || (group + 1 == correct_counts.len() && run == correct_counts[group])
+}
+
+// Ways to finish the records from each state before record `i`, as table[i][group][run]
+fn get_backward_table(records: &str, correct_counts: &[usize]) -> Table {
+    let max_run = correct_counts.iter().max().copied().unwrap_or(0);
+    let mut last = vec![vec![0; max_run + 1]; correct_counts.len() + 1];
+    for (group, runs) in last.iter_mut().enumerate() {
+        for (run, ways) in runs.iter_mut().enumerate() {
+            *ways = is_final_state(correct_counts, group, run) as u128;
+        }
     }
-    let mut ways = 0;
-    let mut current_count = 0;
+    let mut table = vec![last];
+
+    for c in records.chars().rev() {
+        let after = table.last().unwrap();
+        let mut current = after.clone();
+        for (group, runs) in current.iter_mut().enumerate() {
+            for (run, ways) in runs.iter_mut().enumerate() {
+                *ways = get_springs(c)
+                    .iter()
+                    .filter_map(|&spring| next_state(correct_counts, group, run, spring))
+                    .map(|(group, run)| after[group][run])
+                    .sum();
+            }
+        }
+        table.push(current);
+    }
+
+    table.reverse();
+    table
+}
+
+fn count_options(records: &str, correct_counts: &[usize]) -> u128 {
+    let table = get_forward_table(records, correct_counts);
+    let mut result = 0;
+    for (group, runs) in table.last().unwrap().iter().enumerate() {
+        for (run, &ways) in runs.iter().enumerate() {
+            if is_final_state(correct_counts, group, run) {
+                result += ways;
+            }
+        }
+    }
+    result
+}
+
+// How many of the arrangements have a damaged spring in one cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CellDeduction {
+    damaged: u128,
+    total: u128,
+}
+
+impl CellDeduction {
+    fn fraction(&self) -> f64 {
+        self.damaged as f64 / self.total as f64
+    }
+
+    // The spring the cell must be, if every arrangement agrees
+    fn forced(&self) -> Option<char> {
+        match self.damaged {
+            0 => Some('.'),
+            damaged if damaged == self.total => Some('#'),
+            _ => None,
+        }
+    }
+}
+
+// Combines the forward and backward passes: the arrangements with a damaged spring at `i` are those
+// reaching some state before `i` and finishing from where '#' takes it. None if no arrangement fits.
+fn deduce_cells(records: &str, correct_counts: &[usize]) -> Option<Vec<CellDeduction>> {
+    let forward = get_forward_table(records, correct_counts);
+    let backward = get_backward_table(records, correct_counts);
+    let total = backward[0][0][0];
+    if total == 0 {
+        return None;
+    }
+
+    let mut result = vec![];
     for (i, c) in records.chars().enumerate() {
-        match c {
-            '#' => {
-                current_count += 1;
-            }
-            '.' => {
-                if current_count == correct_counts[0] {
-                    let res = ways + count_options(&records[i+1..], &correct_counts[1..], cache);
-                    cache.insert(cache_key, res);
-                    return res;
+        let mut damaged = 0;
+        if c != '.' {
+            for (group, runs) in forward[i].iter().enumerate() {
+                for (run, &ways) in runs.iter().enumerate() {
+                    if let Some((group, run)) = next_state(correct_counts, group, run, '#') {
+                        damaged += ways * backward[i + 1][group][run];
+                    }
                 }
-                if current_count != 0 {
-                    cache.insert(cache_key, ways);
-                    return ways;
+            }
+        }
+        result.push(CellDeduction { damaged, total });
+    }
+    Some(result)
+}
+
+// Every concrete arrangement of the springs, in lexicographic order, found lazily by walking the
+// states that can still be finished.
+struct Arrangements {
+    records: Vec<char>,
+    correct_counts: Vec<usize>,
+    backward: Table,
+    // (springs so far, group, run)
+    stack: Vec<(String, usize, usize)>,
+}
+
+impl Arrangements {
+    fn new(records: &str, correct_counts: &[usize]) -> Self {
+        let backward = get_backward_table(records, correct_counts);
+        let stack = if backward[0][0][0] > 0 {
+            vec![(String::new(), 0, 0)]
+        } else {
+            vec![]
+        };
+        Arrangements {
+            records: records.chars().collect(),
+            correct_counts: correct_counts.to_vec(),
+            backward,
+            stack,
+        }
+    }
+}
+
+impl Iterator for Arrangements {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((springs, group, run)) = self.stack.pop() {
+            let i = springs.len();
+            if i == self.records.len() {
+                return Some(springs);
+            }
+            // Reversed, so that '#' comes off the stack first
+            for &spring in get_springs(self.records[i]).iter().rev() {
+                if let Some((group, run)) = next_state(&self.correct_counts, group, run, spring) {
+                    if self.backward[i + 1][group][run] > 0 {
+                        self.stack.push((format!("{springs}{spring}"), group, run));
+                    }
                 }
             }
-            '?' => {
-                if current_count == 0 {
-                    ways += count_options(&records[i+1..], correct_counts, cache);
+        }
+        None
+    }
+}
+
+// The arrangement at `index` in lexicographic order, stepping over whole subtrees of
+// arrangements using the backward counts.
+fn nth_arrangement(records: &str, correct_counts: &[usize], mut index: u128) -> Option<String> {
+    let backward = get_backward_table(records, correct_counts);
+    if index >= backward[0][0][0] {
+        return None;
+    }
+    let (mut group, mut run) = (0, 0);
+    let mut result = String::new();
+    for (i, c) in records.chars().enumerate() {
+        for &spring in get_springs(c) {
+            let Some((next_group, next_run)) = next_state(correct_counts, group, run, spring)
+            else {
+                continue;
+            };
+            let ways = backward[i + 1][next_group][next_run];
+            if index < ways {
+                (group, run) = (next_group, next_run);
+                result.push(spring);
+                break;
+            }
+            index -= ways;
+        }
+    }
+    Some(result)
+}
+
+// A uniformly random arrangement, given a source of uniformly random u64s
+fn sample_arrangement(
+    records: &str,
+    correct_counts: &[usize],
+    mut random: impl FnMut() -> u64,
+) -> Option<String> {
+    let total = count_options(records, correct_counts);
+    if total == 0 {
+        return None;
+    }
+    let random = ((random() as u128) << 64) | random() as u128;
+    nth_arrangement(records, correct_counts, random % total)
+}
+
+// A nonogram is a grid where every row and column is a spring record
+struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum NonogramSolution {
+    Unique(Vec<String>),
+    // Two of the solutions
+    Multiple(Vec<String>, Vec<String>),
+    None,
+}
+
+impl Nonogram {
+    // Row clues, a blank line, then column clues, one comma separated clue list per line, with `0`
+    // for an empty line of the grid
+    fn parse(input: &str) -> Nonogram {
+        let parse_clues = |block: &str| {
+            block
+                .lines()
+                .map(|line| {
+                    line.split(',')
+                        .map(|s| s.trim().parse::<usize>().unwrap())
+                        .filter(|&count| count > 0)
+                        .collect()
+                })
+                .collect()
+        };
+        let (rows, columns) = input.trim().split_once("\n\n").unwrap();
+        Nonogram {
+            rows: parse_clues(rows),
+            columns: parse_clues(columns),
+        }
+    }
+
+    fn solve(&self) -> NonogramSolution {
+        let grid = vec![vec!['?'; self.columns.len()]; self.rows.len()];
+        let mut solutions = vec![];
+        self.search(grid, &mut solutions);
+        let mut solutions = solutions.into_iter().map(|grid| {
+            grid.into_iter()
+                .map(|row| row.into_iter().collect())
+                .collect()
+        });
+        match (solutions.next(), solutions.next()) {
+            (Some(first), None) => NonogramSolution::Unique(first),
+            (Some(first), Some(second)) => NonogramSolution::Multiple(first, second),
+            _ => NonogramSolution::None,
+        }
+    }
+
+    // Depth first search over the unknown cells, stopping after two solutions
+    fn search(&self, mut grid: Vec<Vec<char>>, solutions: &mut Vec<Vec<Vec<char>>>) {
+        if solutions.len() >= 2 || !self.propagate(&mut grid) {
+            return;
+        }
+        let unknown = grid
+            .iter()
+            .enumerate()
+            .find_map(|(y, row)| row.iter().position(|&c| c == '?').map(|x| (x, y)));
+        let Some((x, y)) = unknown else {
+            solutions.push(grid);
+            return;
+        };
+        for spring in ['#', '.'] {
+            let mut guess = grid.clone();
+            guess[y][x] = spring;
+            self.search(guess, solutions);
+        }
+    }
+
+    // Fills in the cells forced by each row and column on its own until nothing changes. False if
+    // some line has no arrangements left.
+    fn propagate(&self, grid: &mut [Vec<char>]) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (y, correct_counts) in self.rows.iter().enumerate() {
+                let records = grid[y].iter().collect::<String>();
+                let Some(cells) = deduce_cells(&records, correct_counts) else {
+                    return false;
+                };
+                for (x, cell) in cells.iter().enumerate() {
+                    if let (Some(spring), '?') = (cell.forced(), grid[y][x]) {
+                        grid[y][x] = spring;
+                        changed = true;
+                    }
                 }
-                if current_count == correct_counts[0] {
-                    ways += count_options(&records[i+1..], &correct_counts[1..],cache);
+            }
+            for (x, correct_counts) in self.columns.iter().enumerate() {
+                let records = grid.iter().map(|row| row[x]).collect::<String>();
+                let Some(cells) = deduce_cells(&records, correct_counts) else {
+                    return false;
+                };
+                for (y, cell) in cells.iter().enumerate() {
+                    if let (Some(spring), '?') = (cell.forced(), grid[y][x]) {
+                        grid[y][x] = spring;
+                        changed = true;
+                    }
                 }
-                current_count += 1;
             }
-            _ => unreachable!(),
         }
-        
-        if current_count > correct_counts[0] {
-            cache.insert(cache_key, ways);
-            return ways;
+        true
+    }
+}
+
+// The solved grid with each row's clues beside it
+fn render_nonogram(nonogram: &Nonogram, grid: &[String]) -> String {
+    let mut result = String::new();
+    for (row, correct_counts) in grid.iter().zip(&nonogram.rows) {
+        let clues = correct_counts
+            .iter()
+            .map(|count| count.to_string())
+            .collect::<Vec<_>>();
+        result += &format!(
+            "{} {}\n",
+            row.replace('#', "█").replace('.', " "),
+            clues.join(",")
+        );
+    }
+    result
+}
+
+fn unfold(records: &str, correct_counts: &[usize], factor: usize) -> (String, Vec<usize>) {
+    (
+        repeat_n(records, factor).collect::<Vec<_>>().join("?"),
+        repeat_n(correct_counts, factor)
+            .flatten()
+            .copied()
+            .collect(),
+    )
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum SpringError {
+    InvalidSpring {
+        line: usize,
+        column: usize,
+        c: char,
+    },
+    InvalidCount {
+        line: usize,
+        column: usize,
+        count: String,
+    },
+    NoSprings {
+        line: usize,
+    },
+}
+
+impl Display for SpringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpringError::InvalidSpring { line, column, c } => {
+                write!(f, "Invalid spring {c:?} at line {line}, column {column}")
+            }
+            SpringError::InvalidCount {
+                line,
+                column,
+                count,
+            } => write!(f, "Invalid count {count:?} at line {line}, column {column}"),
+            SpringError::NoSprings { line } => write!(f, "No springs at line {line}"),
         }
     }
-    if [current_count] == correct_counts {
-        cache.insert(cache_key, ways + 1);
-        return ways + 1;
+}
+
+// `line` is the 1-based line number for errors. A count list that is empty, missing or a lone `0`
+// means there are no damaged springs, but there has to be at least one spring.
+fn parse_line(line: usize, text: &str) -> Result<(&str, Vec<usize>), SpringError> {
+    let (records, correct_counts) = text.split_once(' ').unwrap_or((text, ""));
+    if records.is_empty() {
+        return Err(SpringError::NoSprings { line });
+    }
+    if let Some((column, c)) = records
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !matches!(c, '#' | '.' | '?'))
+    {
+        return Err(SpringError::InvalidSpring {
+            line,
+            column: column + 1,
+            c,
+        });
+    }
+    if correct_counts.trim().is_empty() || correct_counts.trim() == "0" {
+        return Ok((records, vec![]));
     }
-    cache.insert(cache_key, ways);
-    return ways;
+
+    let mut column = records.len() + 2;
+    let mut result = vec![];
+    for count in correct_counts.split(',') {
+        match count.parse::<usize>() {
+            Ok(parsed) if parsed > 0 => result.push(parsed),
+            _ => {
+                return Err(SpringError::InvalidCount {
+                    line,
+                    column,
+                    count: count.to_string(),
+                })
+            }
+        }
+        column += count.len() + 1;
+    }
+    Ok((records, result))
 }
 
-fn part_one(input: &[&str]) -> usize {
+fn count_all_options(input: &[&str], unfold_factor: usize) -> Result<u128, SpringError> {
     let mut total = 0;
-    for line in input {
-        let (records, correct_counts) = line.split_once(' ').unwrap();
-        let correct_counts = correct_counts
-            .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
-        let mut cache = HashMap::new();
-        let result = count_options(records, &correct_counts, &mut cache);
-        total += result;
+    for (i, line) in input.iter().enumerate() {
+        let (records, correct_counts) = parse_line(i + 1, line)?;
+        let (records, correct_counts) = unfold(records, &correct_counts, unfold_factor);
+        total += count_options(&records, &correct_counts);
     }
-    total
+    Ok(total)
 }
 
-fn part_two(input: &[&str]) -> usize {
-    let mut total = 0;
-    for line in input {
-        let (records, correct_counts) = line.split_once(' ').unwrap();
-        let correct_counts = correct_counts
-            .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect::<Vec<_>>();
+fn part_one(input: &[&str]) -> Result<u128, SpringError> {
+    count_all_options(input, 1)
+}
 
-        let records = repeat(records).take(5).collect::<Vec<_>>().join("?");
-        let correct_counts = repeat(correct_counts).take(5).flatten().collect::<Vec<_>>();
-        let mut cache = HashMap::new();
-        let result = count_options(&records, &correct_counts, &mut cache);
-        total += result;
-    }
-    total
+fn part_two(input: &[&str]) -> Result<u128, SpringError> {
+    count_all_options(input, 5)
 }
 
 fn main() {
+    // --nonogram <clue file>
+    if let Some(path) = std::env::args()
+        .skip_while(|arg| arg != "--nonogram")
+        .nth(1)
+    {
+        let nonogram = Nonogram::parse(&fs::read_to_string(path).unwrap());
+        match nonogram.solve() {
+            NonogramSolution::Unique(grid) => {
+                println!("Unique solution:\n{}", render_nonogram(&nonogram, &grid));
+            }
+            NonogramSolution::Multiple(first, second) => {
+                println!(
+                    "Multiple solutions, including:\n{}",
+                    render_nonogram(&nonogram, &first)
+                );
+                println!("and:\n{}", render_nonogram(&nonogram, &second));
+            }
+            NonogramSolution::None => println!("No solution"),
+        }
+        return;
+    }
     let input = fs::read_to_string("input.txt").unwrap();
     let input = input.lines().collect::<Vec<_>>();
-    println!("Part one: {}", part_one(&input));
-    println!("Part two: {}", part_two(&input));
+    // --examples <line number>
+    if let Some(line) = std::env::args()
+        .skip_while(|arg| arg != "--examples")
+        .nth(1)
+    {
+        let line = line.parse::<usize>().unwrap();
+        let (records, correct_counts) = parse_line(line, input[line - 1]).unwrap();
+        for arrangement in Arrangements::new(records, &correct_counts).take(10) {
+            println!("{arrangement}");
+        }
+        let mut seed = std::process::id() as u64;
+        let random = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            // The low bits of an LCG are poor, so swap the halves
+            seed.rotate_left(32)
+        };
+        if let Some(sample) = sample_arrangement(records, &correct_counts, random) {
+            println!("Random: {sample}");
+        }
+    }
+    // --deduce <line number>
+    if let Some(line) = std::env::args().skip_while(|arg| arg != "--deduce").nth(1) {
+        let line = line.parse::<usize>().unwrap();
+        let (records, correct_counts) = parse_line(line, input[line - 1]).unwrap();
+        match deduce_cells(records, &correct_counts) {
+            Some(cells) => {
+                for (c, cell) in records.chars().zip(cells) {
+                    match cell.forced() {
+                        Some(spring) if c == '?' => println!("? -> {spring}"),
+                        Some(_) => println!("{c}"),
+                        None => println!("? {:.3} damaged", cell.fraction()),
+                    }
+                }
+            }
+            None => println!("No arrangements"),
+        }
+    }
+    match (part_one(&input), part_two(&input)) {
+        (Ok(one), Ok(two)) => {
+            println!("Part one: {one}");
+            println!("Part two: {two}");
+        }
+        (Err(error), _) | (_, Err(error)) => eprintln!("{error}"),
+    }
 }
 
 #[cfg(test)]
@@ -111,28 +557,248 @@ mod tests {
 
     #[test]
     fn test_simple() {
-        assert_eq!(count_options("??", &[1], &mut HashMap::new()), 2);
-        assert_eq!(count_options("???", &[1], &mut HashMap::new()), 3);
-        assert_eq!(count_options("?#?", &[1], &mut HashMap::new()), 1);
-        assert_eq!(count_options("???", &[1, 1], &mut HashMap::new()), 1);
-        assert_eq!(count_options("????", &[1, 2], &mut HashMap::new()), 1);
-        assert_eq!(count_options("??.??", &[1, 2], &mut HashMap::new()), 2);
-        assert_eq!(count_options("???.??", &[1, 2], &mut HashMap::new()), 3);
-        assert_eq!(count_options("???.???", &[1, 2], &mut HashMap::new()), 6);
+        assert_eq!(count_options("??", &[1]), 2);
+        assert_eq!(count_options("???", &[1]), 3);
+        assert_eq!(count_options("?#?", &[1]), 1);
+        assert_eq!(count_options("???", &[1, 1]), 1);
+        assert_eq!(count_options("????", &[1, 2]), 1);
+        assert_eq!(count_options("??.??", &[1, 2]), 2);
+        assert_eq!(count_options("???.??", &[1, 2]), 3);
+        assert_eq!(count_options("???.???", &[1, 2]), 6);
     }
 
     #[test]
     fn test_tricky() {
-        assert_eq!(count_options("??????.??#.", &[2,3], &mut HashMap::new()), 5)
+        assert_eq!(count_options("??????.??#.", &[2, 3]), 5)
     }
 
     #[test]
     fn test_part_one() {
-        assert_eq!(part_one(INPUT), 21);
+        assert_eq!(part_one(INPUT), Ok(21));
     }
 
     #[test]
     fn test_part_two() {
-        assert_eq!(part_two(INPUT), 525152);
+        assert_eq!(part_two(INPUT), Ok(525152));
+    }
+
+    #[test]
+    fn test_large_unfold() {
+        // Any 40 of the 120 gaps between the springs: C(120, 40), well beyond u64
+        let (records, correct_counts) = unfold("???", &[1], 40);
+        let binomial = (0..40).fold(1_u128, |acc, i| acc * (120 - i) / (i + 1));
+        assert!(binomial > u64::MAX as u128);
+        assert_eq!(count_options(&records, &correct_counts), binomial);
+        assert_eq!(count_all_options(&["?###???????? 3,2,1"], 5), Ok(506250));
+        assert_eq!(count_all_options(INPUT, 1), part_one(INPUT));
+    }
+
+    #[test]
+    fn test_arrangements() {
+        assert_eq!(
+            Arrangements::new("???.###", &[1, 1, 3]).collect::<Vec<_>>(),
+            ["#.#.###"]
+        );
+        assert_eq!(
+            Arrangements::new("?###????????", &[3, 2, 1])
+                .take(3)
+                .collect::<Vec<_>>(),
+            [".###.##.#...", ".###.##..#..", ".###.##...#."]
+        );
+        assert_eq!(Arrangements::new("#.#", &[2]).next(), None);
+        assert_eq!(Arrangements::new("..", &[]).collect::<Vec<_>>(), [".."]);
+
+        for line in INPUT {
+            let (records, correct_counts) = parse_line(1, line).unwrap();
+            let arrangements = Arrangements::new(records, &correct_counts).collect::<Vec<_>>();
+            assert_eq!(
+                arrangements.len() as u128,
+                count_options(records, &correct_counts)
+            );
+            assert!(arrangements.windows(2).all(|w| w[0] < w[1]));
+            for (i, arrangement) in arrangements.iter().enumerate() {
+                assert_eq!(
+                    nth_arrangement(records, &correct_counts, i as u128).as_ref(),
+                    Some(arrangement)
+                );
+                assert!(arrangement
+                    .chars()
+                    .zip(records.chars())
+                    .all(|(a, r)| r == '?' || a == r));
+                let groups = arrangement
+                    .split('.')
+                    .filter(|g| !g.is_empty())
+                    .map(|g| g.len())
+                    .collect::<Vec<_>>();
+                assert_eq!(groups, correct_counts);
+            }
+            assert_eq!(
+                nth_arrangement(records, &correct_counts, arrangements.len() as u128),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_arrangement() {
+        let mut seed = 1_u64;
+        let mut random = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            // The low bits of an LCG are poor, so swap the halves
+            seed.rotate_left(32)
+        };
+        let all = Arrangements::new("?###????????", &[3, 2, 1]).collect::<Vec<_>>();
+        let mut seen = vec![0; all.len()];
+        for _ in 0..1000 {
+            let sample = sample_arrangement("?###????????", &[3, 2, 1], &mut random).unwrap();
+            seen[all.iter().position(|a| *a == sample).unwrap()] += 1;
+        }
+        assert!(seen.iter().all(|&count| count > 50));
+
+        let (records, correct_counts) = unfold("?###????????", &[3, 2, 1], 5);
+        assert!(sample_arrangement(&records, &correct_counts, &mut random).is_some());
+        assert_eq!(sample_arrangement("#", &[2], &mut random), None);
+    }
+
+    #[test]
+    fn test_deduce_cells() {
+        let forced = |records: &str, correct_counts: &[usize]| {
+            deduce_cells(records, correct_counts)
+                .unwrap()
+                .iter()
+                .map(|cell| cell.forced().unwrap_or('?'))
+                .collect::<String>()
+        };
+        assert_eq!(forced("???.###", &[1, 1, 3]), "#.#.###");
+        assert_eq!(forced("?###????????", &[3, 2, 1]), ".###.???????");
+        assert_eq!(forced("????", &[1, 2]), "#.##");
+        assert_eq!(forced("?????", &[4]), "?###?");
+        assert_eq!(deduce_cells("#.#", &[2]), None);
+
+        let cells = deduce_cells("???", &[1]).unwrap();
+        assert_eq!(
+            cells[0],
+            CellDeduction {
+                damaged: 1,
+                total: 3
+            }
+        );
+        assert!((cells[1].fraction() - 1.0 / 3.0).abs() < 1e-9);
+
+        // Agrees with counting the enumerated arrangements
+        for line in INPUT {
+            let (records, correct_counts) = parse_line(1, line).unwrap();
+            let (records, correct_counts) = unfold(records, &correct_counts, 2);
+            let arrangements = Arrangements::new(&records, &correct_counts).collect::<Vec<_>>();
+            let cells = deduce_cells(&records, &correct_counts).unwrap();
+            for (i, cell) in cells.iter().enumerate() {
+                let damaged = arrangements
+                    .iter()
+                    .filter(|a| a.as_bytes()[i] == b'#')
+                    .count();
+                assert_eq!(cell.damaged, damaged as u128);
+                assert_eq!(cell.total, arrangements.len() as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nonogram() {
+        // A heart
+        let nonogram = Nonogram::parse(
+            "1,1
+5
+5
+3
+1
+
+2
+4
+4
+4
+2",
+        );
+        assert_eq!(
+            nonogram.solve(),
+            NonogramSolution::Unique(
+                [".#.#.", "#####", "#####", ".###.", "..#.."]
+                    .map(String::from)
+                    .to_vec()
+            )
+        );
+        assert_eq!(
+            render_nonogram(&nonogram, &[".#.#.".to_string()]),
+            " █ █  1,1\n"
+        );
+
+        // Needs a guess: either diagonal fits
+        let nonogram = Nonogram::parse("1\n1\n\n1\n1");
+        let NonogramSolution::Multiple(first, second) = nonogram.solve() else {
+            panic!("Expected multiple solutions");
+        };
+        assert_eq!(first, ["#.", ".#"]);
+        assert_eq!(second, [".#", "#."]);
+
+        let nonogram = Nonogram::parse("2\n0\n\n1\n0");
+        assert_eq!(nonogram.solve(), NonogramSolution::None);
+
+        // Rows and columns that pin each other down
+        let nonogram = Nonogram::parse("1,1\n1\n1,1\n\n1,1\n1\n1,1");
+        assert_eq!(
+            nonogram.solve(),
+            NonogramSolution::Unique(["#.#", ".#.", "#.#"].map(String::from).to_vec())
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            part_one(&["???.### 1,1,3", "??x.# 1,1"]),
+            Err(SpringError::InvalidSpring {
+                line: 2,
+                column: 3,
+                c: 'x'
+            })
+        );
+        assert_eq!(
+            parse_line(4, "??.# 1,x,3"),
+            Err(SpringError::InvalidCount {
+                line: 4,
+                column: 8,
+                count: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse_line(1, "??.# 1,,3").unwrap_err().to_string(),
+            "Invalid count \"\" at line 1, column 8"
+        );
+        assert!(parse_line(1, "??.# 1,0").is_err());
+        assert!(parse_line(1, "??.# -1").is_err());
+        assert_eq!(
+            parse_line(7, "1,1").unwrap_err().to_string(),
+            "Invalid spring '1' at line 7, column 1"
+        );
+        // A blank line isn't a record without damaged springs
+        assert_eq!(
+            part_one(&["???.### 1,1,3", "", "?.? 0"]),
+            Err(SpringError::NoSprings { line: 2 })
+        );
+        assert_eq!(
+            parse_line(3, " 1,2").unwrap_err().to_string(),
+            "No springs at line 3"
+        );
+    }
+
+    #[test]
+    fn test_no_damaged_springs() {
+        assert_eq!(parse_line(1, "?.? 0"), Ok(("?.?", vec![])));
+        assert_eq!(parse_line(1, "?.? "), Ok(("?.?", vec![])));
+        assert_eq!(parse_line(1, "?.?"), Ok(("?.?", vec![])));
+        assert_eq!(part_one(&["?.? 0", "... ", "#.. 0"]), Ok(2));
+        assert_eq!(part_two(&["?.? 0"]), Ok(1));
+        assert_eq!(count_options("", &[]), 1);
+        assert_eq!(deduce_cells("??", &[]).unwrap()[1].forced(), Some('.'));
     }
 }
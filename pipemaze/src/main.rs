@@ -1,6 +1,11 @@
-use std::{fmt::Display, fs, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs,
+    str::FromStr,
+};
 
-use colored::{Colorize, ColoredString};
+use colored::{ColoredString, Colorize};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum Pipe {
@@ -30,6 +35,15 @@ impl Display for Pipe {
     }
 }
 
+const PIPE_SHAPES: [Pipe; 6] = [
+    Pipe::NorthSouth,
+    Pipe::EastWest,
+    Pipe::NorthEast,
+    Pipe::NorthWest,
+    Pipe::SouthEast,
+    Pipe::SouthWest,
+];
+
 impl Pipe {
     fn connects_to(self, delta_x: i32, delta_y: i32) -> bool {
         if delta_x.abs() + delta_y.abs() != 1 {
@@ -45,25 +59,127 @@ impl Pipe {
             _ => false,
         }
     }
+}
+
+#[derive(Debug)]
+struct PipeLoop {
+    tiles: Vec<(i32, i32)>,
+    members: HashSet<(i32, i32)>,
+}
+
+impl PipeLoop {
+    fn new(tiles: Vec<(i32, i32)>) -> Self {
+        let members = tiles.iter().copied().collect();
+        PipeLoop { tiles, members }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        self.members.contains(&(x, y))
+    }
+
+    fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    // Twice the area of the polygon, by the shoelace formula
+    fn double_area(&self) -> i64 {
+        let mut result = 0;
+        for (i, &(x1, y1)) in self.tiles.iter().enumerate() {
+            let (x2, y2) = self.tiles[(i + 1) % self.tiles.len()];
+            result += x1 as i64 * y2 as i64 - x2 as i64 * y1 as i64;
+        }
+        result.abs()
+    }
+
+    // Every tile is a lattice point and the loop tiles are the boundary points, so by Pick's
+    // theorem (A = I + B/2 - 1) the number of enclosed tiles is I = A - B/2 + 1.
+    fn enclosed_tile_count(&self) -> usize {
+        ((self.double_area() - self.len() as i64) / 2 + 1) as usize
+    }
+
+    // Distances of every loop tile from the first one, going either way round
+    fn get_distances(&self) -> Vec<LoopDistance> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(i, &tile)| LoopDistance {
+                tile,
+                forward: i,
+                backward: (self.len() - i) % self.len(),
+            })
+            .collect()
+    }
+
+    // One tile for an even length loop, the two tiles either side of the middle for an odd one
+    fn get_farthest_tiles(&self) -> Vec<(i32, i32)> {
+        let distances = self.get_distances();
+        let farthest = distances
+            .iter()
+            .map(LoopDistance::shortest)
+            .max()
+            .unwrap_or(0);
+        distances
+            .into_iter()
+            .filter(|d| d.shortest() == farthest)
+            .map(|d| d.tile)
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct LoopDistance {
+    tile: (i32, i32),
+    forward: usize,
+    backward: usize,
+}
+
+impl LoopDistance {
+    fn shortest(&self) -> usize {
+        self.forward.min(self.backward)
+    }
+}
 
-    fn from_connections(connections: &[(i32, i32)]) -> Pipe {
-        assert!(connections.len() == 2, "Invalid connections");
-        for pipe in [
-            Pipe::NorthSouth,
-            Pipe::EastWest,
-            Pipe::NorthEast,
-            Pipe::NorthWest,
-            Pipe::SouthEast,
-            Pipe::SouthWest,
-        ] {
-            if connections.iter().all(|(x, y)| pipe.connects_to(*x, *y)) {
-                return pipe;
+#[derive(Debug, PartialEq)]
+enum MazeError {
+    InvalidCharacter { x: usize, y: usize, c: char },
+    NoStartingPoint,
+    MultipleStartingPoints(Vec<(i32, i32)>),
+    NoClosedLoop,
+    // More than one shape of the starting pipe closes a loop
+    AmbiguousLoop(Vec<Pipe>),
+}
+
+impl Display for MazeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MazeError::InvalidCharacter { x, y, c } => {
+                write!(f, "Invalid character {c:?} at ({x}, {y})")
+            }
+            MazeError::NoStartingPoint => write!(f, "No starting point"),
+            MazeError::MultipleStartingPoints(points) => {
+                write!(f, "Multiple starting points at {points:?}")
+            }
+            MazeError::NoClosedLoop => write!(f, "Starting point isn't on a closed loop"),
+            MazeError::AmbiguousLoop(pipes) => {
+                let pipes = pipes.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+                write!(
+                    f,
+                    "Starting point closes a loop as any of {}",
+                    pipes.join(" ")
+                )
             }
         }
-        panic!("Invalid connections")
     }
 }
 
+#[derive(Debug, PartialEq)]
+struct PipeNetwork {
+    // In walking order for loops
+    tiles: Vec<(i32, i32)>,
+    // `None` for open pieces of pipe
+    enclosed_tiles: Option<usize>,
+}
+
 #[derive(Debug)]
 struct Grid {
     grid: Vec<Vec<Pipe>>,
@@ -71,11 +187,11 @@ struct Grid {
 }
 
 impl FromStr for Grid {
-    type Err = ();
+    type Err = MazeError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut grid = Vec::new();
-        let mut starting_point = (0, 0);
+        let mut starting_points = Vec::new();
         for (y, line) in s.lines().enumerate() {
             let mut row = Vec::new();
             for (x, c) in line.chars().enumerate() {
@@ -88,15 +204,20 @@ impl FromStr for Grid {
                     'J' => Pipe::NorthWest,
                     'F' => Pipe::SouthEast,
                     '7' => Pipe::SouthWest,
-                    _ => panic!("Invalid character"),
+                    _ => return Err(MazeError::InvalidCharacter { x, y, c }),
                 };
                 if pipe == Pipe::StartingPoint {
-                    starting_point = (x as i32, y as i32);
+                    starting_points.push((x as i32, y as i32));
                 }
                 row.push(pipe);
             }
             grid.push(row);
         }
+        let starting_point = match starting_points[..] {
+            [] => return Err(MazeError::NoStartingPoint),
+            [starting_point] => starting_point,
+            _ => return Err(MazeError::MultipleStartingPoints(starting_points)),
+        };
         Ok(Grid {
             grid,
             starting_point,
@@ -105,25 +226,32 @@ impl FromStr for Grid {
 }
 
 impl Grid {
-    fn replace_starting_point(&mut self) {
+    // Tries every pipe shape for the starting point, keeping the one that closes a loop.
+    fn replace_starting_point(&mut self) -> Result<(), MazeError> {
         let (start_x, start_y) = self.starting_point;
 
-        let mut connections = vec![];
-
-        for (x, y) in [
-            (start_x - 1, start_y),
-            (start_x + 1, start_y),
-            (start_x, start_y - 1),
-            (start_x, start_y + 1),
-        ] {
-            if let Some(pipe) = self.get(x, y) {
-                if pipe.connects_to(start_x - x, start_y - y) {
-                    connections.push((x - start_x, y - start_y));
+        let closing_pipes = PIPE_SHAPES
+            .into_iter()
+            .filter(|&pipe| {
+                self.grid[start_y as usize][start_x as usize] = pipe;
+                self.follow_loop().is_some()
+            })
+            .collect::<Vec<_>>();
+
+        match closing_pipes[..] {
+            [pipe] => {
+                self.grid[start_y as usize][start_x as usize] = pipe;
+                Ok(())
+            }
+            _ => {
+                self.grid[start_y as usize][start_x as usize] = Pipe::StartingPoint;
+                if closing_pipes.is_empty() {
+                    Err(MazeError::NoClosedLoop)
+                } else {
+                    Err(MazeError::AmbiguousLoop(closing_pipes))
                 }
             }
         }
-
-        self.grid[start_y as usize][start_x as usize] = Pipe::from_connections(&connections[..]);
     }
 
     fn get(&self, x: i32, y: i32) -> Option<&Pipe> {
@@ -136,33 +264,105 @@ impl Grid {
     }
 
     fn get_loop(&self) -> Vec<(i32, i32)> {
+        self.follow_loop()
+            .expect("Starting point isn't on a closed loop")
+    }
+
+    fn follow_loop(&self) -> Option<Vec<(i32, i32)>> {
+        self.follow_loop_from(self.starting_point)
+    }
+
+    // The tiles of the loop through `start`, or `None` if the pipes starting from it don't lead
+    // back to it.
+    fn follow_loop_from(&self, start: (i32, i32)) -> Option<Vec<(i32, i32)>> {
         let mut result = vec![];
-        let mut pos = self.starting_point;
+        let mut pos = start;
         let mut came_from = (0, 0);
-        while pos != self.starting_point || result.is_empty() {
+        while pos != start || result.is_empty() {
             let (x, y) = pos;
-            let pipe = self.get(x, y).unwrap();
-            for (delta_x, delta_y) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
-                if (delta_x, delta_y) == came_from {
+            let pipe = self.get(x, y)?;
+            let (delta_x, delta_y) = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .into_iter()
+                .find(|&delta| delta != came_from && pipe.connects_to(delta.0, delta.1))?;
+            let next = (x + delta_x, y + delta_y);
+            if !self.get(next.0, next.1)?.connects_to(-delta_x, -delta_y) {
+                return None;
+            }
+            result.push(pos);
+            pos = next;
+            came_from = (-delta_x, -delta_y);
+        }
+        Some(result)
+    }
+
+    // Neighbours whose pipes connect to the pipe at (x, y), and it to them
+    fn get_connected_neighbours(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        let Some(pipe) = self.get(x, y) else {
+            return Vec::new();
+        };
+        [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .into_iter()
+            .filter(|&(delta_x, delta_y)| {
+                pipe.connects_to(delta_x, delta_y)
+                    && self
+                        .get(x + delta_x, y + delta_y)
+                        .is_some_and(|other| other.connects_to(-delta_x, -delta_y))
+            })
+            .map(|(delta_x, delta_y)| (x + delta_x, y + delta_y))
+            .collect()
+    }
+
+    // Every group of connected pipes in the grid. A group where every pipe connects at both ends
+    // is a closed loop. The starting point should be replaced first, or it counts as a lone piece.
+    fn get_networks(&self) -> Vec<PipeNetwork> {
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, pipe) in row.iter().enumerate() {
+                let start = (x as i32, y as i32);
+                if *pipe == Pipe::Ground || !seen.insert(start) {
                     continue;
                 }
-                if pipe.connects_to(delta_x, delta_y) {
-                    result.push(pos);
-                    pos = (x + delta_x, y + delta_y);
-                    came_from = (-delta_x, -delta_y);
-                    break;
+
+                let mut tiles = vec![];
+                let mut closed = true;
+                let mut stack = vec![start];
+                while let Some((x, y)) = stack.pop() {
+                    tiles.push((x, y));
+                    let neighbours = self.get_connected_neighbours(x, y);
+                    closed &= neighbours.len() == 2;
+                    for neighbour in neighbours {
+                        if seen.insert(neighbour) {
+                            stack.push(neighbour);
+                        }
+                    }
                 }
+
+                result.push(if closed {
+                    let pipe_loop = PipeLoop::new(self.follow_loop_from(start).unwrap());
+                    PipeNetwork {
+                        enclosed_tiles: Some(pipe_loop.enclosed_tile_count()),
+                        tiles: pipe_loop.tiles,
+                    }
+                } else {
+                    PipeNetwork {
+                        tiles,
+                        enclosed_tiles: None,
+                    }
+                });
             }
         }
         result
     }
 
+    // Ray casting, kept to check the faster ways of finding the inside against
+    #[cfg(test)]
     fn count_loop_hits(
         &self,
         pos: (i32, i32),
         step_x: i32,
         step_y: i32,
-        loop_tiles: &[(i32, i32)],
+        pipe_loop: &PipeLoop,
     ) -> usize {
         let mut result = 0;
 
@@ -172,7 +372,7 @@ impl Grid {
         while let Some(pipe) = self.get(x, y) {
             x += step_x;
             y += step_y;
-            if loop_tiles.contains(&(x - step_x, y - step_y)) {
+            if pipe_loop.contains(x - step_x, y - step_y) {
                 match (pipe, step_x.abs(), step_y.abs()) {
                     (Pipe::NorthSouth, 1, _) | (Pipe::EastWest, _, 1) => result += 1,
                     (Pipe::NorthSouth, _, 1) | (Pipe::EastWest, 1, _) => continue,
@@ -220,51 +420,268 @@ impl Grid {
         result
     }
 
-    fn is_contained_by_loop(&self, x: i32, y: i32, loop_tiles: &[(i32, i32)]) -> bool {
-        if loop_tiles.contains(&(x, y)) {
+    #[cfg(test)]
+    fn is_contained_by_loop(&self, x: i32, y: i32, pipe_loop: &PipeLoop) -> bool {
+        if pipe_loop.contains(x, y) {
             return false;
         }
-        self.count_loop_hits((x, y), 1, 0, loop_tiles) % 2 == 1
+        self.count_loop_hits((x, y), 1, 0, pipe_loop) % 2 == 1
+    }
+
+    // Scans each row once, flipping between outside and inside on every loop pipe that reaches
+    // north: `|`, `L` and `J`. A run like `L-7` crosses the loop once, `L-J` doesn't.
+    fn get_inside_tiles(&self, pipe_loop: &PipeLoop) -> Vec<Vec<bool>> {
+        let mut result = Vec::new();
+        for (y, row) in self.grid.iter().enumerate() {
+            let mut inside = false;
+            let mut row_result = Vec::new();
+            for (x, pipe) in row.iter().enumerate() {
+                if pipe_loop.contains(x as i32, y as i32) {
+                    if pipe.connects_to(0, -1) {
+                        inside = !inside;
+                    }
+                    row_result.push(false);
+                } else {
+                    row_result.push(inside);
+                }
+            }
+            result.push(row_result);
+        }
+        result
+    }
+
+    // Flood fills from outside the grid on a doubled-resolution grid, where tile (x, y) becomes
+    // cell (2x + 1, 2y + 1) and the cells between tiles are only blocked by loop pipes joining
+    // them, so the fill squeezes between pipes that don't connect. Returns which tiles the fill
+    // reached; the tiles neither reached nor on the loop are enclosed.
+    fn get_reachable_tiles(&self, pipe_loop: &PipeLoop) -> Vec<Vec<bool>> {
+        let (width, height) = (self.grid[0].len() * 2 + 1, self.grid.len() * 2 + 1);
+        let mut blocked = vec![vec![false; width]; height];
+        for &(x, y) in &pipe_loop.tiles {
+            let (cell_x, cell_y) = (2 * x as usize + 1, 2 * y as usize + 1);
+            blocked[cell_y][cell_x] = true;
+            for (delta_x, delta_y) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                if self.get(x, y).unwrap().connects_to(delta_x, delta_y) {
+                    blocked[(cell_y as i32 + delta_y) as usize]
+                        [(cell_x as i32 + delta_x) as usize] = true;
+                }
+            }
+        }
+
+        let mut reached = vec![vec![false; width]; height];
+        reached[0][0] = true;
+        let mut stack = vec![(0, 0)];
+        while let Some((x, y)) = stack.pop() {
+            for (delta_x, delta_y) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                let (next_x, next_y) = (x as i32 + delta_x, y as i32 + delta_y);
+                if next_x < 0 || next_y < 0 || next_x >= width as i32 || next_y >= height as i32 {
+                    continue;
+                }
+                let (next_x, next_y) = (next_x as usize, next_y as usize);
+                if !blocked[next_y][next_x] && !reached[next_y][next_x] {
+                    reached[next_y][next_x] = true;
+                    stack.push((next_x, next_y));
+                }
+            }
+        }
+
+        (0..self.grid.len())
+            .map(|y| {
+                (0..self.grid[0].len())
+                    .map(|x| reached[2 * y + 1][2 * x + 1])
+                    .collect()
+            })
+            .collect()
     }
 }
 
 fn part_one(input: &str) -> usize {
     let mut grid = Grid::from_str(input).unwrap();
 
-    grid.replace_starting_point();
+    grid.replace_starting_point().unwrap();
     grid.get_loop().len() / 2
 }
 
 fn part_two(input: &str) -> usize {
     let mut grid = Grid::from_str(input).unwrap();
-    grid.replace_starting_point();
+    grid.replace_starting_point().unwrap();
 
-    let grid_loop = grid.get_loop();
+    PipeLoop::new(grid.get_loop()).enclosed_tile_count()
+}
 
-    let mut result = 0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderMode {
+    // Loop in box drawing characters, other tiles as I (inside) or O (outside)
+    Plain,
+    // Every pipe, with the loop in red and inside tiles in blue
+    Ansi,
+    // Inside and outside tiles as filled squares, with the loop drawn over them
+    Svg,
+}
+
+impl FromStr for RenderMode {
+    type Err = ();
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(RenderMode::Plain),
+            "ansi" => Ok(RenderMode::Ansi),
+            "svg" => Ok(RenderMode::Svg),
+            _ => Err(()),
+        }
+    }
+}
+
+// Shortest distance from the start for every loop tile, padded to the same width, `.` elsewhere
+fn render_distances(grid: &Grid, pipe_loop: &PipeLoop) -> String {
+    let distances = pipe_loop
+        .get_distances()
+        .into_iter()
+        .map(|d| (d.tile, d.shortest()))
+        .collect::<HashMap<_, _>>();
+    let width = distances.values().max().unwrap_or(&0).to_string().len();
+
+    let mut output = String::new();
     for y in 0..grid.grid.len() {
-        for x in 0..grid.grid[0].len() {
-            let mut tile_str: ColoredString = grid.get(x as i32, y as i32).unwrap().to_string().into();
-            if grid.is_contained_by_loop(x as i32, y as i32, &grid_loop) {
-                tile_str = tile_str.blue();
-                result += 1;
+        let row = (0..grid.grid[0].len())
+            .map(|x| match distances.get(&(x as i32, y as i32)) {
+                Some(distance) => format!("{distance:>width$}"),
+                None => format!("{:>width$}", "."),
+            })
+            .collect::<Vec<_>>();
+        output += &row.join(" ");
+        output.push('\n');
+    }
+    output
+}
+
+const SVG_TILE_SIZE: usize = 10;
+
+fn render(grid: &Grid, pipe_loop: &PipeLoop, mode: RenderMode) -> String {
+    let inside_tiles = grid.get_inside_tiles(pipe_loop);
+    let mut output = String::new();
+
+    if mode == RenderMode::Svg {
+        let (width, height) = (grid.grid[0].len(), grid.grid.len());
+        output += &format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+            width * SVG_TILE_SIZE,
+            height * SVG_TILE_SIZE
+        );
+        output.push('\n');
+        for (y, row) in inside_tiles.iter().enumerate() {
+            for (x, &inside) in row.iter().enumerate() {
+                if pipe_loop.contains(x as i32, y as i32) {
+                    continue;
+                }
+                output += &format!(
+                    r#"<rect class="{}" x="{}" y="{}" width="{SVG_TILE_SIZE}" height="{SVG_TILE_SIZE}" fill="{}"/>"#,
+                    if inside { "inside" } else { "outside" },
+                    x * SVG_TILE_SIZE,
+                    y * SVG_TILE_SIZE,
+                    if inside { "#4a7fd4" } else { "#eeeeee" }
+                );
+                output.push('\n');
             }
-            if grid_loop.contains(&(x as i32, y as i32)) {
-                tile_str = tile_str.red();
+        }
+        let points = pipe_loop
+            .tiles
+            .iter()
+            .map(|&(x, y)| {
+                format!(
+                    "{},{}",
+                    x as usize * SVG_TILE_SIZE + SVG_TILE_SIZE / 2,
+                    y as usize * SVG_TILE_SIZE + SVG_TILE_SIZE / 2
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        output += &format!(
+            r##"<polygon class="loop" points="{points}" fill="none" stroke="#d43a3a" stroke-width="2"/>"##
+        );
+        output += "\n</svg>\n";
+        return output;
+    }
+
+    for (y, row) in inside_tiles.iter().enumerate() {
+        for (x, &inside) in row.iter().enumerate() {
+            let pipe = grid.get(x as i32, y as i32).unwrap();
+            let on_loop = pipe_loop.contains(x as i32, y as i32);
+            match mode {
+                RenderMode::Plain if on_loop => output += &pipe.to_string(),
+                RenderMode::Plain if inside => output.push('I'),
+                RenderMode::Plain => output.push('O'),
+                _ => {
+                    let mut tile_str: ColoredString = pipe.to_string().into();
+                    if inside {
+                        tile_str = tile_str.blue();
+                    }
+                    if on_loop {
+                        tile_str = tile_str.red();
+                    }
+                    output += &tile_str.to_string();
+                }
             }
-            print!("{}", tile_str);
         }
-        println!();
+        output.push('\n');
     }
 
-    result
+    output
 }
 
 fn main() {
     let input = fs::read_to_string("input.txt").unwrap();
     println!("Part one: {}", part_one(&input));
     println!("Part two: {}", part_two(&input));
+
+    // --render plain|ansi|svg
+    if let Some(mode) = std::env::args().skip_while(|arg| arg != "--render").nth(1) {
+        let mode = mode.parse().expect("Render mode is plain, ansi or svg");
+        let mut grid = Grid::from_str(&input).unwrap();
+        grid.replace_starting_point().unwrap();
+        let pipe_loop = PipeLoop::new(grid.get_loop());
+        print!("{}", render(&grid, &pipe_loop, mode));
+    }
+
+    if std::env::args().any(|arg| arg == "--flood") {
+        let mut grid = Grid::from_str(&input).unwrap();
+        grid.replace_starting_point().unwrap();
+        let pipe_loop = PipeLoop::new(grid.get_loop());
+        let reachable = grid
+            .get_reachable_tiles(&pipe_loop)
+            .iter()
+            .flatten()
+            .filter(|&&r| r)
+            .count();
+        let total = grid.grid.len() * grid.grid[0].len();
+        println!(
+            "Flood fill: {reachable} tiles reachable from outside, {} enclosed",
+            total - reachable - pipe_loop.len()
+        );
+    }
+
+    if std::env::args().any(|arg| arg == "--distances") {
+        let mut grid = Grid::from_str(&input).unwrap();
+        grid.replace_starting_point().unwrap();
+        let pipe_loop = PipeLoop::new(grid.get_loop());
+        println!("Farthest tiles: {:?}", pipe_loop.get_farthest_tiles());
+        print!("{}", render_distances(&grid, &pipe_loop));
+    }
+
+    if std::env::args().any(|arg| arg == "--networks") {
+        let mut grid = Grid::from_str(&input).unwrap();
+        grid.replace_starting_point().unwrap();
+        for network in grid.get_networks() {
+            let (x, y) = network.tiles[0];
+            match network.enclosed_tiles {
+                Some(enclosed) => println!(
+                    "Loop at ({x}, {y}): {} pipes, {enclosed} tiles inside",
+                    network.tiles.len()
+                ),
+                None => println!("Open pipes at ({x}, {y}): {} pipes", network.tiles.len()),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -381,4 +798,267 @@ L7JLJL-JLJLJL--JLJ.L"#
             10
         );
     }
+
+    const ORACLE_INPUTS: &[&str] = &[
+        r#"...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+..........."#,
+        r#".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ..."#,
+        r#"FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L"#,
+    ];
+
+    #[test]
+    fn test_inside_matches_ray_casting() {
+        for input in ORACLE_INPUTS {
+            let mut grid = Grid::from_str(input).unwrap();
+            grid.replace_starting_point().unwrap();
+            let grid_loop = PipeLoop::new(grid.get_loop());
+            let inside_tiles = grid.get_inside_tiles(&grid_loop);
+
+            let mut count = 0;
+            for (y, row) in inside_tiles.iter().enumerate() {
+                for (x, &inside) in row.iter().enumerate() {
+                    assert_eq!(
+                        inside,
+                        grid.is_contained_by_loop(x as i32, y as i32, &grid_loop)
+                    );
+                    count += inside as usize;
+                }
+            }
+            assert_eq!(grid_loop.enclosed_tile_count(), count);
+        }
+    }
+
+    fn render_input(input: &str, mode: RenderMode) -> String {
+        let mut grid = Grid::from_str(input).unwrap();
+        grid.replace_starting_point().unwrap();
+        let pipe_loop = PipeLoop::new(grid.get_loop());
+        render(&grid, &pipe_loop, mode)
+    }
+
+    #[test]
+    fn test_render_plain() {
+        assert_eq!(
+            render_input(
+                r#"-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF"#,
+                RenderMode::Plain
+            ),
+            "OOOOO\nO┌─┐O\nO│I│O\nO└─┘O\nOOOOO\n"
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        colored::control::set_override(false);
+        assert_eq!(
+            render_input(ORACLE_INPUTS[0], RenderMode::Ansi)
+                .lines()
+                .nth(1),
+            Some(".┌───────┐.")
+        );
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let svg = render_input(ORACLE_INPUTS[0], RenderMode::Svg);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="110" height="90">"#)
+        );
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches(r#"class="inside""#).count(), 4);
+        assert_eq!(svg.matches(r#"class="outside""#).count(), 11 * 9 - 46 - 4);
+        assert!(svg.contains(r#"<polygon class="loop" points="15,15 25,15 "#));
+    }
+
+    #[test]
+    fn test_starting_point_errors() {
+        assert_eq!(
+            Grid::from_str(".....\n.F-7.\n.|.|.\n.L-J.\n.....").unwrap_err(),
+            MazeError::NoStartingPoint
+        );
+        assert_eq!(
+            Grid::from_str(".....\n.S-7.\n.|.|.\n.L-S.\n.....").unwrap_err(),
+            MazeError::MultipleStartingPoints(vec![(1, 1), (3, 3)])
+        );
+        assert_eq!(
+            Grid::from_str(".S-7.\n.|.X.").unwrap_err(),
+            MazeError::InvalidCharacter { x: 3, y: 1, c: 'X' }
+        );
+
+        let mut grid = Grid::from_str(".....\n.S-7.\n.|.|.\n.L-..\n.....").unwrap();
+        assert_eq!(grid.replace_starting_point(), Err(MazeError::NoClosedLoop));
+        let mut grid = Grid::from_str(".S.").unwrap();
+        assert_eq!(grid.replace_starting_point(), Err(MazeError::NoClosedLoop));
+        assert_eq!(grid.grid[0][1], Pipe::StartingPoint);
+    }
+
+    #[test]
+    fn test_ambiguous_starting_point() {
+        // S has three connecting neighbours, but only the west and south ones close the loop
+        let mut grid = Grid::from_str("F-S-.\n|.|..\nL-J..").unwrap();
+        grid.replace_starting_point().unwrap();
+        assert_eq!(grid.grid[0][2], Pipe::SouthWest);
+        assert_eq!(grid.get_loop().len(), 8);
+
+        // Two loops meet at S
+        let mut grid = Grid::from_str("F-7..\n|.|..\nL-S-7\n..|.|\n..L-J").unwrap();
+        assert!(matches!(
+            grid.replace_starting_point(),
+            Err(MazeError::AmbiguousLoop(_))
+        ));
+    }
+
+    #[test]
+    fn test_networks() {
+        let mut grid = Grid::from_str(
+            r#"S-7.F7.
+|.|.LJ.
+L-J.-7|
+.F---J.
+.L----7"#,
+        )
+        .unwrap();
+        grid.replace_starting_point().unwrap();
+        let networks = grid.get_networks();
+
+        let loops = networks
+            .iter()
+            .filter(|n| n.enclosed_tiles.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].tiles.len(), 8);
+        assert_eq!(loops[0].enclosed_tiles, Some(1));
+        assert_eq!(loops[1].tiles, vec![(4, 0), (5, 0), (5, 1), (4, 1)]);
+        assert_eq!(loops[1].enclosed_tiles, Some(0));
+
+        let mut fragments = networks
+            .iter()
+            .filter(|n| n.enclosed_tiles.is_none())
+            .map(|n| n.tiles.len())
+            .collect::<Vec<_>>();
+        fragments.sort();
+        assert_eq!(fragments, vec![1, 13]);
+    }
+
+    #[test]
+    fn test_networks_debris() {
+        let mut grid = Grid::from_str(ORACLE_INPUTS[2]).unwrap();
+        grid.replace_starting_point().unwrap();
+        let main_loop = grid.get_loop();
+        let networks = grid.get_networks();
+        let network = networks
+            .iter()
+            .find(|n| n.tiles.contains(&grid.starting_point))
+            .unwrap();
+        assert_eq!(network.tiles.len(), main_loop.len());
+        assert_eq!(network.enclosed_tiles, Some(10));
+        assert_eq!(
+            networks.iter().map(|n| n.tiles.len()).sum::<usize>(),
+            grid.grid
+                .iter()
+                .flatten()
+                .filter(|&&p| p != Pipe::Ground)
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_flood_fill_matches_ray_casting() {
+        let squeezed = r#"..........
+.S------7.
+.|F----7|.
+.||OOOO||.
+.||OOOO||.
+.|L-7F-J|.
+.|II||II|.
+.L--JL--J.
+.........."#
+            .replace(['I', 'O'], ".");
+        for input in ORACLE_INPUTS.iter().copied().chain([squeezed.as_str()]) {
+            let mut grid = Grid::from_str(input).unwrap();
+            grid.replace_starting_point().unwrap();
+            let pipe_loop = PipeLoop::new(grid.get_loop());
+            let reachable = grid.get_reachable_tiles(&pipe_loop);
+
+            let mut enclosed = 0;
+            for (y, row) in reachable.iter().enumerate() {
+                for (x, &reached) in row.iter().enumerate() {
+                    let on_loop = pipe_loop.contains(x as i32, y as i32);
+                    let inside = grid.is_contained_by_loop(x as i32, y as i32, &pipe_loop);
+                    assert_eq!(reached, !on_loop && !inside);
+                    enclosed += (!reached && !on_loop) as usize;
+                }
+            }
+            assert_eq!(enclosed, pipe_loop.enclosed_tile_count());
+        }
+    }
+
+    #[test]
+    fn test_distances() {
+        let mut grid = Grid::from_str(
+            r#"..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ..."#,
+        )
+        .unwrap();
+        grid.replace_starting_point().unwrap();
+        let pipe_loop = PipeLoop::new(grid.get_loop());
+        assert_eq!(pipe_loop.get_farthest_tiles(), vec![(4, 2)]);
+        assert_eq!(
+            render_distances(&grid, &pipe_loop),
+            r#". . 4 5 .
+. 2 3 6 .
+0 1 . 7 8
+1 4 5 6 7
+2 3 . . .
+"#
+        );
+        let distances = pipe_loop.get_distances();
+        assert_eq!(
+            distances[1],
+            LoopDistance {
+                tile: (1, 2),
+                forward: 1,
+                backward: 15
+            }
+        );
+        assert_eq!(distances[0].shortest(), 0);
+    }
+
+    #[test]
+    fn test_distances_odd_loop() {
+        // Loops on a square grid are always even, so only a hand-made polygon can be odd
+        let pipe_loop = PipeLoop::new(vec![(0, 0), (1, 0), (2, 0), (2, 1), (1, 1)]);
+        assert_eq!(pipe_loop.get_farthest_tiles(), vec![(2, 0), (2, 1)]);
+    }
 }
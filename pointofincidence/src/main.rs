@@ -1,71 +1,365 @@
 use std::fs;
 
-fn get_line_bitmap(line: &str) -> u64 {
-    let mut bitmap = 0;
-    for c in line.chars() {
-        bitmap <<= 1;
+// A line of cells as bits, the first cell in the lowest bit, for lines of any length
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bitset {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitset {
+    fn new(len: usize) -> Bitset {
+        Bitset {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    fn ones(len: usize) -> Bitset {
+        let mut bitset = Bitset::new(len);
+        for i in 0..len {
+            bitset.set(i);
+        }
+        bitset
+    }
+
+    fn set(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    // The 64 bits starting at `offset`, zero past the end
+    fn word_at(&self, offset: usize) -> u64 {
+        let (word, bit) = (offset / 64, offset % 64);
+        let low = self.words.get(word).copied().unwrap_or(0) >> bit;
+        if bit == 0 {
+            return low;
+        }
+        low | self.words.get(word + 1).copied().unwrap_or(0) << (64 - bit)
+    }
+
+    // Whether `count` bits from `offset` match the `count` bits from `other_offset` in `other`,
+    // a word at a time
+    fn range_eq(&self, offset: usize, other: &Bitset, other_offset: usize, count: usize) -> bool {
+        let mut checked = 0;
+        while checked < count {
+            let n = (count - checked).min(64);
+            let mask = if n == 64 { u64::MAX } else { (1 << n) - 1 };
+            let diff = self.word_at(offset + checked) ^ other.word_at(other_offset + checked);
+            if diff & mask != 0 {
+                return false;
+            }
+            checked += n;
+        }
+        true
+    }
+
+    fn and_assign(&mut self, other: &Bitset) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= other;
+        }
+    }
+
+    fn xor(&self, other: &Bitset) -> Bitset {
+        Bitset {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(a, b)| a ^ b)
+                .collect(),
+            len: self.len,
+        }
+    }
+
+    // Indices of the set bits, lowest first
+    fn ones_iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+}
+
+fn get_line_bitmap(line: &str) -> Bitset {
+    let mut bitmap = Bitset::new(line.len());
+    for (i, c) in line.chars().enumerate() {
         if c == '#' {
-            bitmap |= 1;
+            bitmap.set(i);
         }
     }
     bitmap
 }
 
-fn get_horizontal_reflection_map(line: &str) -> u64 {
+// Bit `i` is set if the line reflects across the axis with `i` cells before it
+fn get_horizontal_reflection_map(line: &str) -> Bitset {
     let forward_bitmap = get_line_bitmap(line);
     let reverse_bitmap = get_line_bitmap(&line.chars().rev().collect::<String>());
-    let mut reflection_map = 0;
     let len = line.len();
+    let mut reflection_map = Bitset::new(len);
     for i in 1..len {
-        let mask = ((1 << i) - 1) & ((1 << len - i) - 1);
-        if (forward_bitmap >> i) & mask == (reverse_bitmap >> (len - i)) & mask {
-            reflection_map |= 1 << (len - i);
+        // Cell i + d against cell i - 1 - d, which is len - i + d in the reversed line
+        if forward_bitmap.range_eq(i, &reverse_bitmap, len - i, i.min(len - i)) {
+            reflection_map.set(i);
         }
     }
     reflection_map
 }
 
-fn get_reflection_score(lines: &[&str], avoid_score: Option<u32>) -> Option<u32> {
-    let line_length = lines[0].len();
-    let mut reflection_map = (1 << line_length) - 1;
+// Axes that every line reflects across
+fn get_common_reflection_map(lines: &[&str]) -> Bitset {
+    let mut reflection_map = Bitset::ones(lines[0].len());
     for line in lines {
-        reflection_map &= get_horizontal_reflection_map(line);
+        reflection_map.and_assign(&get_horizontal_reflection_map(line));
     }
-    if let Some(avoid_score) = avoid_score {
-        if avoid_score < 100 {
-            let mask = (1 << line_length) - 1 ^ (1 << avoid_score);
-            reflection_map &= mask;
+    reflection_map
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Orientation {
+    // A mirror between two columns
+    Vertical,
+    // A mirror between two rows
+    Horizontal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Axis {
+    orientation: Orientation,
+    // Columns to the left or rows above the mirror
+    index: usize,
+}
+
+impl Axis {
+    fn score(&self) -> u32 {
+        match self.orientation {
+            Orientation::Vertical => self.index as u32,
+            Orientation::Horizontal => self.index as u32 * 100,
         }
     }
-    if reflection_map != 0 {
-        let result = Some(reflection_map.trailing_zeros());
-        return result;
+}
+
+fn as_strs(lines: &[String]) -> Vec<&str> {
+    lines.iter().map(|line| line.as_str()).collect()
+}
+
+// Every axis the pattern reflects across, vertical ones first, each in order
+fn get_reflection_axes(lines: &[&str]) -> Vec<Axis> {
+    let vertical = get_common_reflection_map(lines)
+        .ones_iter()
+        .map(|index| Axis {
+            orientation: Orientation::Vertical,
+            index,
+        })
+        .collect::<Vec<_>>();
+    let transposed_lines = transpose(lines);
+    let horizontal = get_common_reflection_map(&as_strs(&transposed_lines))
+        .ones_iter()
+        .map(|index| Axis {
+            orientation: Orientation::Horizontal,
+            index,
+        })
+        .collect::<Vec<_>>();
+    [vertical, horizontal].concat()
+}
+
+fn get_reflection_score(lines: &[&str]) -> Option<u32> {
+    get_reflection_axes(lines).first().map(|axis| axis.score())
+}
+
+// The pattern with the axis marked in the margins, like in the puzzle text
+fn render(lines: &[&str], axis: Axis) -> String {
+    let mut result = String::new();
+    match axis.orientation {
+        Orientation::Vertical => {
+            let numbers = (1..=lines[0].len())
+                .map(|i| char::from_digit(i as u32 % 10, 10).unwrap())
+                .collect::<String>();
+            let markers = format!("{}><", " ".repeat(axis.index - 1));
+            result += &format!("{numbers}\n{markers}\n");
+            for line in lines {
+                result += &format!("{line}\n");
+            }
+            result += &format!("{markers}\n{numbers}\n");
+        }
+        Orientation::Horizontal => {
+            let width = lines.len().to_string().len();
+            for (i, line) in lines.iter().enumerate() {
+                let marker = match i + 1 {
+                    n if n == axis.index => 'v',
+                    n if n == axis.index + 1 => '^',
+                    _ => ' ',
+                };
+                result += &format!("{:>width$}{marker}{line}{marker}{}\n", i + 1, i + 1);
+            }
+        }
     }
-    // transpose
-    let transposed_lines = (0..line_length)
+    result
+}
+
+fn transpose(lines: &[&str]) -> Vec<String> {
+    (0..lines[0].len())
         .map(|i| {
             lines
                 .iter()
                 .map(|line| line.chars().nth(i).unwrap())
                 .collect::<String>()
         })
+        .collect()
+}
+
+// Mismatching cells across each axis between lines, as (index along the line, line index) of the
+// cell before the axis. The axis at `i` has `i` lines before it.
+fn get_axis_mismatches(lines: &[&str], max_mismatches: usize) -> Vec<Option<Vec<(usize, usize)>>> {
+    let bitmaps = lines
+        .iter()
+        .map(|line| get_line_bitmap(line))
         .collect::<Vec<_>>();
-    let transposed_line_length = transposed_lines[0].len();
-    let mut reflection_map = (1 << transposed_line_length) - 1;
-    for line in transposed_lines {
-        reflection_map &= get_horizontal_reflection_map(&line);
-    }
-    if let Some(avoid_score) = avoid_score {
-        if avoid_score >= 100 {
-            let mask = (1 << transposed_line_length) - 1 ^ (1 << avoid_score / 100);
-            reflection_map &= mask;
+    let mut result = vec![None];
+    for i in 1..lines.len() {
+        let mut mismatches = vec![];
+        for (before, after) in (0..i).rev().zip(i..lines.len()) {
+            let diff = bitmaps[before].xor(&bitmaps[after]);
+            for x in diff.ones_iter() {
+                if mismatches.len() > max_mismatches {
+                    break;
+                }
+                mismatches.push((x, before));
+            }
         }
+        result.push((mismatches.len() <= max_mismatches).then_some(mismatches));
+    }
+    result
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SmudgedReflection {
+    axis: Axis,
+    // (x, y) of each cell that has to flip for the reflection, on the top or left side of the axis
+    smudges: Vec<(usize, usize)>,
+}
+
+// Every reflection that needs exactly `smudges` cells flipped, in the same order as
+// get_reflection_axes, counting the mismatches across each axis instead of trying every flip
+fn get_smudged_reflections(lines: &[&str], smudges: usize) -> Vec<SmudgedReflection> {
+    let transposed_lines = transpose(lines);
+    let vertical = get_axis_mismatches(&as_strs(&transposed_lines), smudges)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, mismatches)| {
+            let mismatches = mismatches.filter(|m| m.len() == smudges)?;
+            Some(SmudgedReflection {
+                axis: Axis {
+                    orientation: Orientation::Vertical,
+                    index,
+                },
+                smudges: mismatches.into_iter().map(|(y, x)| (x, y)).collect(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let horizontal = get_axis_mismatches(lines, smudges)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, mismatches)| {
+            let mismatches = mismatches.filter(|m| m.len() == smudges)?;
+            Some(SmudgedReflection {
+                axis: Axis {
+                    orientation: Orientation::Horizontal,
+                    index,
+                },
+                smudges: mismatches,
+            })
+        });
+    vertical.into_iter().chain(horizontal).collect()
+}
+
+fn find_smudged_reflection(lines: &[&str], smudges: usize) -> Option<SmudgedReflection> {
+    get_smudged_reflections(lines, smudges).into_iter().next()
+}
+
+// The pattern turned 180 degrees
+fn rotate_half(lines: &[&str]) -> Vec<String> {
+    lines
+        .iter()
+        .rev()
+        .map(|line| line.chars().rev().collect())
+        .collect()
+}
+
+// The pattern turned 90 degrees clockwise
+fn rotate_quarter(lines: &[&str]) -> Vec<String> {
+    transpose(lines)
+        .iter()
+        .map(|line| line.chars().rev().collect())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symmetry {
+    HalfTurn,
+    QuarterTurn,
+    // A mirror from the top left corner to the bottom right
+    MainDiagonal,
+    // A mirror from the top right corner to the bottom left
+    AntiDiagonal,
+}
+
+// Symmetries of the whole pattern other than the axis mirrors. Only square patterns can have the
+// quarter turn and diagonal ones.
+fn get_symmetries(lines: &[&str]) -> Vec<Symmetry> {
+    let mut result = vec![];
+    if rotate_half(lines) == lines {
+        result.push(Symmetry::HalfTurn);
+    }
+    if lines.len() != lines[0].len() {
+        return result;
+    }
+    if rotate_quarter(lines) == lines {
+        result.push(Symmetry::QuarterTurn);
+    }
+    let transposed_lines = transpose(lines);
+    if transposed_lines == lines {
+        result.push(Symmetry::MainDiagonal);
     }
-    if reflection_map == 0 {
-        return None;
+    if rotate_half(&as_strs(&transposed_lines)) == lines {
+        result.push(Symmetry::AntiDiagonal);
     }
-    let result = Some(reflection_map.trailing_zeros() * 100);
-    return result;
+    result
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Square {
+    x: usize,
+    y: usize,
+    size: usize,
+}
+
+// The largest square within the pattern holding both ash and rock that looks the same after a
+// quarter turn, since a solid block always does. The first in reading order if there are several.
+fn find_quarter_turn_square(lines: &[&str]) -> Option<Square> {
+    let (width, height) = (lines[0].len(), lines.len());
+    for size in (2..=width.min(height)).rev() {
+        for y in 0..=height - size {
+            for x in 0..=width - size {
+                let square = lines[y..y + size]
+                    .iter()
+                    .map(|line| &line[x..x + size])
+                    .collect::<Vec<_>>();
+                let mixed = square.iter().any(|line| line.contains('#'))
+                    && square.iter().any(|line| line.contains('.'));
+                if mixed && rotate_quarter(&square) == square {
+                    return Some(Square { x, y, size });
+                }
+            }
+        }
+    }
+    None
 }
 
 fn part_one(input: &str) -> u32 {
@@ -75,7 +369,7 @@ fn part_one(input: &str) -> u32 {
         .iter()
         .map(|p| {
             let lines = p.lines().collect::<Vec<&str>>();
-            get_reflection_score(&lines, None).unwrap()
+            get_reflection_score(&lines).unwrap()
         })
         .sum()
 }
@@ -86,23 +380,11 @@ fn part_two(input: &str) -> u32 {
     parts
         .iter()
         .map(|p| {
-            let score_without_smudge = {
-                let lines = p.lines().collect::<Vec<&str>>();
-                get_reflection_score(&lines, None).unwrap()
-            };
-            for i in 0..p.len() {
-                let mut desmudged = p.to_string();
-                match desmudged.chars().nth(i).unwrap() {
-                    '#' => desmudged.replace_range(i..i + 1, "."),
-                    '.' => desmudged.replace_range(i..i + 1, "#"),
-                    _ => continue,
-                }
-                let lines = desmudged.lines().collect::<Vec<&str>>();
-                if let Some(score) = get_reflection_score(&lines, Some(score_without_smudge)) {
-                    return score;
-                }
+            let lines = p.lines().collect::<Vec<&str>>();
+            match find_smudged_reflection(&lines, 1) {
+                Some(reflection) => reflection.axis.score(),
+                None => panic!("No smudge found for {:?}", p),
             }
-            panic!("No smudge found for {:?}", p);
         })
         .sum()
 }
@@ -111,6 +393,27 @@ fn main() {
     let input = fs::read_to_string("input.txt").unwrap();
     println!("Part one: {}", part_one(&input));
     println!("Part two: {}", part_two(&input));
+
+    if std::env::args().any(|arg| arg == "--render") {
+        for pattern in input.split("\n\n") {
+            let lines = pattern.lines().collect::<Vec<_>>();
+            for axis in get_reflection_axes(&lines) {
+                println!("{}", render(&lines, axis));
+            }
+        }
+    }
+
+    if std::env::args().any(|arg| arg == "--symmetry") {
+        for (i, pattern) in input.split("\n\n").enumerate() {
+            let lines = pattern.lines().collect::<Vec<_>>();
+            let symmetries = get_symmetries(&lines);
+            let square = find_quarter_turn_square(&lines);
+            println!(
+                "Pattern {}: {symmetries:?}, quarter turn square {square:?}",
+                i + 1
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -128,7 +431,7 @@ mod tests {
             "..##..##.",
             "#.#.##.#.",
         ];
-        assert_eq!(get_reflection_score(&input, None), Some(5));
+        assert_eq!(get_reflection_score(&input), Some(5));
     }
 
     #[test]
@@ -142,7 +445,7 @@ mod tests {
             "..##..###",
             "#....#..#",
         ];
-        assert_eq!(get_reflection_score(&input, None), Some(400));
+        assert_eq!(get_reflection_score(&input), Some(400));
     }
 
     #[test]
@@ -163,7 +466,310 @@ mod tests {
             ".####..", "###..#.", "..#.###", "#.####.", "#.####.", "..#.###", "###..#.", ".####..",
             "...#.#.", "...#.#.", ".####..", "###..#.", "..#.###",
         ];
-        assert_eq!(get_reflection_score(&input, None), Some(400));
-        assert_eq!(get_reflection_score(&input, Some(400)), Some(900));
+        assert_eq!(get_reflection_score(&input), Some(400));
+        assert_eq!(
+            get_reflection_axes(&input),
+            [
+                Axis {
+                    orientation: Orientation::Horizontal,
+                    index: 4
+                },
+                Axis {
+                    orientation: Orientation::Horizontal,
+                    index: 9
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_smudged_reflection() {
+        let input = fs::read_to_string("example.txt").unwrap();
+        let patterns = input
+            .split("\n\n")
+            .map(|p| p.lines().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            find_smudged_reflection(&patterns[0], 0),
+            Some(SmudgedReflection {
+                axis: Axis {
+                    orientation: Orientation::Vertical,
+                    index: 5
+                },
+                smudges: vec![]
+            })
+        );
+        assert_eq!(
+            find_smudged_reflection(&patterns[0], 1),
+            Some(SmudgedReflection {
+                axis: Axis {
+                    orientation: Orientation::Horizontal,
+                    index: 3
+                },
+                smudges: vec![(0, 0)]
+            })
+        );
+        assert_eq!(
+            find_smudged_reflection(&patterns[1], 1),
+            Some(SmudgedReflection {
+                axis: Axis {
+                    orientation: Orientation::Horizontal,
+                    index: 1
+                },
+                smudges: vec![(4, 0)]
+            })
+        );
+
+        let lines = vec!["#..#", "....", ".#.#"];
+        let reflection = find_smudged_reflection(&lines, 2).unwrap();
+        assert_eq!(reflection.axis.score(), 1);
+        assert_eq!(reflection.smudges.len(), 2);
+        assert_eq!(find_smudged_reflection(&["#.", ".#"], 0), None);
+
+        let tricky = vec![
+            ".####..", "###..#.", "..#.###", "#.####.", "#.####.", "..#.###", "###..#.", ".####..",
+            "...#.#.", "...#.#.", ".####..", "###..#.", "..#.###",
+        ];
+        assert_eq!(
+            find_smudged_reflection(&tricky, 0).unwrap().axis.score(),
+            400
+        );
+    }
+
+    #[test]
+    fn test_smudges_fix_reflection() {
+        // Flipping the reported cells leaves a pattern with a perfect reflection on the same axis
+        let input = fs::read_to_string("example.txt").unwrap();
+        for pattern in input.split("\n\n") {
+            let lines = pattern.lines().collect::<Vec<_>>();
+            for smudges in 0..3 {
+                let Some(reflection) = find_smudged_reflection(&lines, smudges) else {
+                    continue;
+                };
+                let mut fixed = lines
+                    .iter()
+                    .map(|l| l.chars().collect::<Vec<_>>())
+                    .collect::<Vec<_>>();
+                for &(x, y) in &reflection.smudges {
+                    fixed[y][x] = if fixed[y][x] == '#' { '.' } else { '#' };
+                }
+                let fixed = fixed
+                    .iter()
+                    .map(|l| l.iter().collect::<String>())
+                    .collect::<Vec<_>>();
+                let fixed = fixed.iter().map(|l| l.as_str()).collect::<Vec<_>>();
+                let lines = match reflection.axis.orientation {
+                    Orientation::Vertical => transpose(&fixed),
+                    Orientation::Horizontal => fixed.iter().map(|l| l.to_string()).collect(),
+                };
+                let lines = lines.iter().map(|l| l.as_str()).collect::<Vec<_>>();
+                assert_eq!(
+                    get_axis_mismatches(&lines, 0)[reflection.axis.index],
+                    Some(vec![])
+                );
+            }
+        }
+    }
+
+    // A `width` by `height` pattern that reflects across the axis after `axis` columns, with
+    // pseudo-random cells elsewhere
+    fn wide_pattern(width: usize, height: usize, axis: usize, seed: u64) -> Vec<String> {
+        let mut state = seed;
+        (0..height)
+            .map(|_| {
+                let mut line = (0..width)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        if state >> 63 == 1 {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                for d in 0..axis.min(width - axis) {
+                    line[axis + d] = line[axis - 1 - d];
+                }
+                line.into_iter().collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wide_bitset() {
+        let line = "#".repeat(130) + "." + &"#".repeat(69);
+        let bitmap = get_line_bitmap(&line);
+        assert_eq!(bitmap.ones_iter().count(), 199);
+        assert_eq!(bitmap.ones_iter().next(), Some(0));
+        assert!(!bitmap.ones_iter().any(|i| i == 130));
+        assert_eq!(bitmap.word_at(100) & (1 << 30), 0);
+        assert!(bitmap.range_eq(0, &bitmap, 131, 69));
+        assert!(!bitmap.range_eq(0, &bitmap, 100, 64));
+
+        let map = get_horizontal_reflection_map(&("#".repeat(100) + &".".repeat(100)));
+        assert_eq!(
+            map.ones_iter().collect::<Vec<_>>(),
+            (1..=50).chain(150..200).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_wide_patterns() {
+        for (axis, seed) in [(137, 1), (63, 2), (64, 3), (65, 4), (1, 5), (199, 6)] {
+            let pattern = wide_pattern(200, 17, axis, seed);
+            let lines = pattern.iter().map(|l| l.as_str()).collect::<Vec<_>>();
+            assert_eq!(get_reflection_score(&lines), Some(axis as u32));
+            assert_eq!(
+                find_smudged_reflection(&lines, 0),
+                Some(SmudgedReflection {
+                    axis: Axis {
+                        orientation: Orientation::Vertical,
+                        index: axis
+                    },
+                    smudges: vec![]
+                })
+            );
+
+            // Rotated, the axis becomes horizontal in a 200 tall pattern
+            let transposed = transpose(&lines);
+            let transposed = transposed.iter().map(|l| l.as_str()).collect::<Vec<_>>();
+            assert_eq!(get_reflection_score(&transposed), Some(axis as u32 * 100));
+
+            // A single flipped cell on the far side of the axis is found as the smudge
+            let mut smudged = pattern.clone();
+            let x = axis + axis.min(200 - axis) / 2;
+            let flipped = if smudged[3].as_bytes()[x] == b'#' {
+                "."
+            } else {
+                "#"
+            };
+            smudged[3].replace_range(x..x + 1, flipped);
+            let lines = smudged.iter().map(|l| l.as_str()).collect::<Vec<_>>();
+            let reflection = find_smudged_reflection(&lines, 1).unwrap();
+            assert_eq!(reflection.axis.score(), axis as u32);
+            assert_eq!(reflection.smudges, vec![(2 * axis - 1 - x, 3)]);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let input = fs::read_to_string("example.txt").unwrap();
+        let patterns = input
+            .split("\n\n")
+            .map(|p| p.lines().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            render(&patterns[0], get_reflection_axes(&patterns[0])[0]),
+            "123456789
+    ><
+#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+    ><
+123456789
+"
+        );
+        assert_eq!(
+            render(&patterns[1], get_reflection_axes(&patterns[1])[0]),
+            "1 #...##..# 1
+2 #....#..# 2
+3 ..##..### 3
+4v#####.##.v4
+5^#####.##.^5
+6 ..##..### 6
+7 #....#..# 7
+"
+        );
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let input = fs::read_to_string("example.txt").unwrap();
+        for pattern in input.split("\n\n") {
+            assert_eq!(get_symmetries(&pattern.lines().collect::<Vec<_>>()), []);
+        }
+
+        assert_eq!(
+            get_symmetries(&["#..", "...", "..#"]),
+            [
+                Symmetry::HalfTurn,
+                Symmetry::MainDiagonal,
+                Symmetry::AntiDiagonal
+            ]
+        );
+        assert_eq!(
+            get_symmetries(&["#.", ".#"]),
+            [
+                Symmetry::HalfTurn,
+                Symmetry::MainDiagonal,
+                Symmetry::AntiDiagonal
+            ]
+        );
+        assert_eq!(get_symmetries(&["##.", "...", ".##"]), [Symmetry::HalfTurn]);
+        assert_eq!(
+            get_symmetries(&["#.#", ".#.", "#.#"]),
+            [
+                Symmetry::HalfTurn,
+                Symmetry::QuarterTurn,
+                Symmetry::MainDiagonal,
+                Symmetry::AntiDiagonal
+            ]
+        );
+        // A pinwheel turns but has no mirrors
+        assert_eq!(
+            get_symmetries(&["##.#", "...#", "#...", "#.##"]),
+            [Symmetry::HalfTurn, Symmetry::QuarterTurn]
+        );
+        assert_eq!(get_symmetries(&["#.", "#."]), []);
+        assert_eq!(get_symmetries(&["#..#", ".##."]), []);
+        assert_eq!(get_symmetries(&["#...", "...#"]), [Symmetry::HalfTurn]);
+        assert_eq!(get_symmetries(&["..#", "#.."]), [Symmetry::HalfTurn]);
+    }
+
+    #[test]
+    fn test_quarter_turn_square() {
+        assert_eq!(find_quarter_turn_square(&["#.", "#."]), None);
+        // Solid blocks don't count
+        assert_eq!(find_quarter_turn_square(&["#..", "...", "..#"]), None);
+        assert_eq!(find_quarter_turn_square(&["##.", "##.", "..."]), None);
+        assert_eq!(
+            find_quarter_turn_square(&[".#..", "###.", ".#.#"]),
+            Some(Square {
+                x: 0,
+                y: 0,
+                size: 3
+            })
+        );
+        let input = fs::read_to_string("example.txt").unwrap();
+        let patterns = input
+            .split("\n\n")
+            .map(|p| p.lines().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(find_quarter_turn_square(&patterns[0]), None);
+        assert_eq!(
+            find_quarter_turn_square(&patterns[1]),
+            Some(Square {
+                x: 1,
+                y: 2,
+                size: 4
+            })
+        );
+        // The pinwheel from test_symmetries, set into a bigger pattern
+        assert_eq!(
+            find_quarter_turn_square(
+                &["#.#.#..#", "..###.##", "#.#...#.", ".#.#....", "##.#.##.",]
+            ),
+            Some(Square {
+                x: 3,
+                y: 1,
+                size: 4
+            })
+        );
     }
 }
@@ -1,110 +1,156 @@
-use std::fs;
-
-fn perform_shift(input: &[String]) -> Vec<String> {
-    let mut result: Vec<String> = Vec::new();
-    let mut support_levels = input[0].chars().map(|_c| -1).collect::<Vec<i64>>();
-    for (i, line) in input.iter().enumerate() {
-        let mut new_line = String::new();
-        for (j, c) in line.chars().enumerate() {
-            match c {
-                '#' => {
-                    support_levels[j] = i as i64;
-                    new_line.push('#');
+use std::{collections::HashMap, fs};
+
+// The platform as one byte per cell, row by row
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Platform {
+    cells: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+impl Platform {
+    fn parse(input: &[&str]) -> Platform {
+        let mut cells = Vec::with_capacity(input.len() * input[0].len());
+        for line in input {
+            for c in line.bytes() {
+                match c {
+                    b'#' | b'.' | b'O' => cells.push(c),
+                    _ => panic!("Unknown character {}", c as char),
                 }
-                '.' => {
-                    new_line.push('.');
+            }
+        }
+        Platform {
+            cells,
+            width: input[0].len(),
+            height: input.len(),
+        }
+    }
+
+    // North and south sweep row by row, keeping the next free row for every column, so the
+    // cells are read in memory order
+    fn tilt_north(&mut self) {
+        let width = self.width;
+        let mut free = vec![0; width];
+        for y in 0..self.height {
+            for (x, free) in free.iter_mut().enumerate() {
+                match self.cells[y * width + x] {
+                    b'#' => *free = y + 1,
+                    b'O' => {
+                        self.cells[y * width + x] = b'.';
+                        self.cells[*free * width + x] = b'O';
+                        *free += 1;
+                    }
+                    _ => {}
                 }
-                'O' => {
-                    support_levels[j] += 1;
-                    if support_levels[j] as usize == i {
-                        new_line.push('O');
-                    } else {
-                        new_line.push('.');
-                        unsafe {
-                            result[support_levels[j] as usize].as_bytes_mut()[j] = b'O';
-                        }
+            }
+        }
+    }
+
+    fn tilt_south(&mut self) {
+        let width = self.width;
+        // One past the next free row
+        let mut free = vec![self.height; width];
+        for y in (0..self.height).rev() {
+            for (x, free) in free.iter_mut().enumerate() {
+                match self.cells[y * width + x] {
+                    b'#' => *free = y,
+                    b'O' => {
+                        *free -= 1;
+                        self.cells[y * width + x] = b'.';
+                        self.cells[*free * width + x] = b'O';
                     }
+                    _ => {}
                 }
-                _ => panic!("Unknown character {c}"),
             }
         }
-        result.push(new_line);
     }
-    result
-}
 
-fn rotate_90(input: &[String]) -> Vec<String> {
-    let mut result: Vec<String> = Vec::new();
-    for (i, line) in input.iter().enumerate() {
-        let mut new_line = String::new();
-        for (j, _c) in line.chars().enumerate() {
-            new_line.push(input[input.len() - j - 1].chars().nth(i).unwrap());
+    fn tilt_west(&mut self) {
+        for row in self.cells.chunks_mut(self.width) {
+            let mut free = 0;
+            for x in 0..row.len() {
+                match row[x] {
+                    b'#' => free = x + 1,
+                    b'O' => {
+                        row[x] = b'.';
+                        row[free] = b'O';
+                        free += 1;
+                    }
+                    _ => {}
+                }
+            }
         }
-        result.push(new_line);
     }
-    result
-}
 
-fn calculate_load_level(input: &[String]) -> u64 {
-    let mut total_load = 0;
-    for (i, line) in input.iter().enumerate() {
-        for (_j, c) in line.chars().enumerate() {
-            match c {
-                '#' | '.' => {}
-                'O' => {
-                    total_load += input.len() as u64 - i as u64;
+    fn tilt_east(&mut self) {
+        for row in self.cells.chunks_mut(self.width) {
+            // One past the next free column
+            let mut free = row.len();
+            for x in (0..row.len()).rev() {
+                match row[x] {
+                    b'#' => free = x,
+                    b'O' => {
+                        free -= 1;
+                        row[x] = b'.';
+                        row[free] = b'O';
+                    }
+                    _ => {}
                 }
-                _ => panic!("Unknown character {c}"),
             }
         }
     }
-    total_load
-}
 
-fn part_one(input: &[&str]) -> u64 {
-    let shifted = perform_shift(
-        input
-            .iter()
-            .map(|s| (*s).to_string())
-            .collect::<Vec<String>>()
-            .as_slice(),
-    );
-    calculate_load_level(&shifted)
+    fn perform_cycle(&mut self) {
+        self.tilt_north();
+        self.tilt_west();
+        self.tilt_south();
+        self.tilt_east();
+    }
+
+    fn calculate_load_level(&self) -> u64 {
+        self.cells
+            .chunks(self.width)
+            .enumerate()
+            .map(|(i, row)| {
+                let rocks = row.iter().filter(|&&c| c == b'O').count() as u64;
+                rocks * (self.height - i) as u64
+            })
+            .sum()
+    }
+
+    #[cfg(test)]
+    fn to_lines(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.width)
+            .map(|row| String::from_utf8(row.to_vec()).unwrap())
+            .collect()
+    }
 }
 
-fn perform_cycle(input: &[String]) -> Vec<String> {
-    let mut result = perform_shift(input);
-    result = rotate_90(&result);
-    result = perform_shift(&result);
-    result = rotate_90(&result);
-    result = perform_shift(&result);
-    result = rotate_90(&result);
-    result = perform_shift(&result);
-    result = rotate_90(&result);
-    result
+fn part_one(input: &[&str]) -> u64 {
+    let mut platform = Platform::parse(input);
+    platform.tilt_north();
+    platform.calculate_load_level()
 }
 
 fn part_two(input: &[&str]) -> u64 {
-    let mut hash_map = std::collections::HashMap::new();
-    let mut intermediate_value = input
-        .iter()
-        .map(|s| (*s).to_string())
-        .collect::<Vec<String>>();
-    hash_map.insert(intermediate_value.join(""), 0);
+    let mut hash_map = HashMap::new();
+    let mut platform = Platform::parse(input);
+    hash_map.insert(platform.clone(), 0);
 
     for iter in 1..=1_000_000 {
-        intermediate_value = perform_cycle(&intermediate_value);
-        let existing = hash_map.insert(intermediate_value.join(""), iter);
+        platform.perform_cycle();
+        let existing = hash_map.insert(platform.clone(), iter);
         if let Some(previous_iter) = existing {
             let loop_lenght = iter - previous_iter;
             let remaining_cycles = (1_000_000_000 - iter) % loop_lenght;
             for _ in 0..remaining_cycles {
-                intermediate_value = perform_cycle(&intermediate_value);
+                platform.perform_cycle();
             }
-            return calculate_load_level(&intermediate_value);
+            return platform.calculate_load_level();
         }
     }
-    calculate_load_level(&intermediate_value)
+    platform.calculate_load_level()
 }
 
 fn main() {
@@ -131,6 +177,89 @@ mod tests {
         "#OO..#....",
     ];
 
+    // The original string based tilt, which only rolls north and rotates the platform for the
+    // other directions, as a reference
+    fn perform_shift(input: &[String]) -> Vec<String> {
+        let mut result: Vec<String> = Vec::new();
+        let mut support_levels = input[0].chars().map(|_c| -1).collect::<Vec<i64>>();
+        for (i, line) in input.iter().enumerate() {
+            let mut new_line = String::new();
+            for (j, c) in line.chars().enumerate() {
+                match c {
+                    '#' => {
+                        support_levels[j] = i as i64;
+                        new_line.push('#');
+                    }
+                    '.' => {
+                        new_line.push('.');
+                    }
+                    'O' => {
+                        support_levels[j] += 1;
+                        if support_levels[j] as usize == i {
+                            new_line.push('O');
+                        } else {
+                            new_line.push('.');
+                            unsafe {
+                                result[support_levels[j] as usize].as_bytes_mut()[j] = b'O';
+                            }
+                        }
+                    }
+                    _ => panic!("Unknown character {c}"),
+                }
+            }
+            result.push(new_line);
+        }
+        result
+    }
+
+    fn rotate_90(input: &[String]) -> Vec<String> {
+        let mut result: Vec<String> = Vec::new();
+        for (i, line) in input.iter().enumerate() {
+            let mut new_line = String::new();
+            for (j, _c) in line.chars().enumerate() {
+                new_line.push(input[input.len() - j - 1].chars().nth(i).unwrap());
+            }
+            result.push(new_line);
+        }
+        result
+    }
+
+    fn perform_string_cycle(input: &[String]) -> Vec<String> {
+        let mut result = perform_shift(input);
+        result = rotate_90(&result);
+        result = perform_shift(&result);
+        result = rotate_90(&result);
+        result = perform_shift(&result);
+        result = rotate_90(&result);
+        result = perform_shift(&result);
+        result = rotate_90(&result);
+        result
+    }
+
+    fn generate_platform(width: usize, height: usize, seed: u64) -> Vec<String> {
+        let mut state = seed;
+        (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        match state >> 61 {
+                            0 => '#',
+                            1 | 2 => 'O',
+                            _ => '.',
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn as_strs(lines: &[String]) -> Vec<&str> {
+        lines.iter().map(|line| line.as_str()).collect()
+    }
+
     #[test]
     fn test_part_one() {
         assert_eq!(part_one(TEST_INPUT), 136);
@@ -140,4 +269,113 @@ mod tests {
     fn test_part_two() {
         assert_eq!(part_two(TEST_INPUT), 64);
     }
+
+    #[test]
+    fn test_tilts() {
+        let mut platform = Platform::parse(&["O.O#.O", "..O..."]);
+        platform.tilt_west();
+        assert_eq!(platform.to_lines(), ["OO.#O.", "O....."]);
+        platform.tilt_east();
+        assert_eq!(platform.to_lines(), [".OO#.O", ".....O"]);
+        platform.tilt_south();
+        assert_eq!(platform.to_lines(), ["...#.O", ".OO..O"]);
+        platform.tilt_north();
+        assert_eq!(platform.to_lines(), [".OO#.O", ".....O"]);
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut platform = Platform::parse(TEST_INPUT);
+        let expected = [
+            [
+                ".....#....",
+                "....#...O#",
+                "...OO##...",
+                ".OO#......",
+                ".....OOO#.",
+                ".O#...O#.#",
+                "....O#....",
+                "......OOOO",
+                "#...O###..",
+                "#..OO#....",
+            ],
+            [
+                ".....#....",
+                "....#...O#",
+                ".....##...",
+                "..O#......",
+                ".....OOO#.",
+                ".O#...O#.#",
+                "....O#...O",
+                ".......OOO",
+                "#..OO###..",
+                "#.OOO#...O",
+            ],
+            [
+                ".....#....",
+                "....#...O#",
+                ".....##...",
+                "..O#......",
+                ".....OOO#.",
+                ".O#...O#.#",
+                "....O#...O",
+                ".......OOO",
+                "#...O###.O",
+                "#.OOO#...O",
+            ],
+        ];
+        for lines in expected {
+            platform.perform_cycle();
+            assert_eq!(platform.to_lines(), lines);
+        }
+    }
+
+    #[test]
+    fn test_matches_string_cycle() {
+        // rotate_90 only handles square platforms
+        for (width, height, seed) in [(7, 7, 1), (12, 12, 2), (30, 30, 3)] {
+            let mut lines = generate_platform(width, height, seed);
+            let mut platform = Platform::parse(&as_strs(&lines));
+            for _ in 0..5 {
+                lines = perform_string_cycle(&lines);
+                platform.perform_cycle();
+                assert_eq!(platform.to_lines(), lines);
+            }
+            lines = perform_shift(&lines);
+            platform.tilt_north();
+            assert_eq!(platform.to_lines(), lines);
+        }
+    }
+
+    // cargo test --release -- --ignored --nocapture bench_cycle
+    #[test]
+    #[ignore]
+    fn bench_cycle() {
+        use std::time::Instant;
+
+        let lines = generate_platform(500, 500, 500);
+        let mut string_lines = lines.clone();
+        let now = Instant::now();
+        for _ in 0..10 {
+            string_lines = perform_string_cycle(&string_lines);
+        }
+        let string_time = now.elapsed();
+
+        let mut platform = Platform::parse(&as_strs(&lines));
+        let now = Instant::now();
+        for _ in 0..10 {
+            platform.perform_cycle();
+        }
+        let byte_time = now.elapsed();
+        assert_eq!(platform.to_lines(), string_lines);
+
+        let now = Instant::now();
+        for _ in 0..1000 {
+            platform.perform_cycle();
+        }
+        let many_time = now.elapsed();
+        println!(
+            "500x500, 10 cycles: strings {string_time:?}, bytes {byte_time:?}; 1000 cycles on bytes {many_time:?}"
+        );
+    }
 }